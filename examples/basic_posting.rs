@@ -21,8 +21,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         sensitive: true,
         spoiler_text: Some("Big surprise content".to_string()),
         visibility: Some("unlisted".to_string()),
-        in_reply_to_id: None,
         language: Some("en".to_string()),
+        ..Default::default()
     };
     client.statuses().create(&params).await?;
 
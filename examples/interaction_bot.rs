@@ -20,14 +20,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             interval.tick().await;
             println!("Posting periodic status update...");
             let stats = client_clone.instance().activity().await;
-            if let Ok(activity) = stats {
-                if let Some(week) = activity.first() {
-                    let msg = format!(
-                        "🤖 Weekly Instance Stats:\nStatuses: {}\nLogins: {}\nNew Registrations: {}",
-                        week.statuses, week.logins, week.registrations
-                    );
-                    let _ = client_clone.statuses().create_simple(&msg).await;
-                }
+            if let Ok(activity) = stats
+                && let Some(week) = activity.first()
+            {
+                let msg = format!(
+                    "🤖 Weekly Instance Stats:\nStatuses: {}\nLogins: {}\nNew Registrations: {}",
+                    week.statuses, week.logins, week.registrations
+                );
+                let _ = client_clone.statuses().create_simple(&msg).await;
             }
         }
     });
@@ -61,13 +61,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         // Auto-follow back if they aren't followed yet
                         let rel = client
                             .accounts()
-                            .relationships(&[notification.account.id.clone()])
+                            .relationships(std::slice::from_ref(&notification.account.id))
                             .await?;
-                        if let Some(r) = rel.first() {
-                            if !r.following {
-                                client.accounts().follow(&notification.account.id).await?;
-                                println!("Followed back @{}", notification.account.username);
-                            }
+                        if let Some(r) = rel.first()
+                            && !r.following
+                        {
+                            client.accounts().follow(&notification.account.id).await?;
+                            println!("Followed back @{}", notification.account.username);
                         }
                     }
                     _ => {}
@@ -24,12 +24,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 );
 
                 // Auto-mention back
-                if notif.r#type == "mention" {
-                    if let Some(_status) = notif.status {
-                        let reply_text =
-                            format!("@{}: Thanks for the mention!", notif.account.username);
-                        client.statuses().create_simple(&reply_text).await?;
-                    }
+                if notif.r#type == "mention"
+                    && let Some(_status) = notif.status
+                {
+                    let reply_text =
+                        format!("@{}: Thanks for the mention!", notif.account.username);
+                    client.statuses().create_simple(&reply_text).await?;
                 }
             }
             Ok(MastodonEvent::Delete(id)) => {
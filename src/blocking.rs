@@ -0,0 +1,473 @@
+//! A synchronous client for scripts and command-line tools.
+//!
+//! [`BlockingMastodonClient`] wraps a [`MastodonClient`] together with a private
+//! single-threaded tokio runtime, and mirrors its handlers with methods that block
+//! until the request has completed. Models and errors are the same as for the async
+//! client.
+//!
+//! The blocking client must not be used from within an async runtime; calling it from
+//! an async task panics. Use [`MastodonClient`] there instead.
+//!
+//! Enabled with the `blocking` feature.
+
+use crate::MastodonClient;
+use crate::error::Result;
+use crate::methods::accounts::RegisterAccountParams;
+use crate::methods::statuses::CreateStatusParams;
+use crate::models::{Account, Context, MediaAttachment, Notification, Relationship, Status};
+use crate::oauth::Token;
+use crate::response::Response;
+use std::future::Future;
+use tokio::runtime::Runtime;
+
+/// A synchronous Mastodon client.
+///
+/// # Example
+/// ```no_run
+/// use mastodon_api::blocking::BlockingMastodonClient;
+///
+/// fn main() -> mastodon_api::Result<()> {
+///     let client = BlockingMastodonClient::new("https://mastodon.social").with_token("your_token");
+///     let status = client.statuses().create_simple("Hello from a script!")?;
+///     println!("Posted {}", status.url.unwrap_or_default());
+///     Ok(())
+/// }
+/// ```
+pub struct BlockingMastodonClient {
+    inner: MastodonClient,
+    runtime: Runtime,
+}
+
+impl BlockingMastodonClient {
+    /// Creates a new `BlockingMastodonClient` for the given instance URL.
+    ///
+    /// # Panics
+    /// Panics if the internal runtime cannot be created.
+    pub fn new(instance_url: &str) -> Self {
+        Self::from_client(MastodonClient::new(instance_url))
+    }
+
+    /// Wraps an async client, keeping its configuration.
+    ///
+    /// # Panics
+    /// Panics if the internal runtime cannot be created.
+    pub fn from_client(client: MastodonClient) -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to create the runtime of the blocking client");
+        Self {
+            inner: client,
+            runtime,
+        }
+    }
+
+    /// Sets the access token for the client, enabling authenticated requests.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.inner = self.inner.with_token(token);
+        self
+    }
+
+    /// Returns the underlying async client.
+    pub fn inner(&self) -> &MastodonClient {
+        &self.inner
+    }
+
+    /// Returns the base URL of the Mastodon instance.
+    pub fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Access account-related endpoints.
+    pub fn accounts(&self) -> BlockingAccountsHandler<'_> {
+        BlockingAccountsHandler { client: self }
+    }
+
+    /// Access status-related endpoints.
+    pub fn statuses(&self) -> BlockingStatusesHandler<'_> {
+        BlockingStatusesHandler { client: self }
+    }
+
+    /// Access timeline-related endpoints.
+    pub fn timelines(&self) -> BlockingTimelinesHandler<'_> {
+        BlockingTimelinesHandler { client: self }
+    }
+
+    /// Access media-related endpoints.
+    pub fn media(&self) -> BlockingMediaHandler<'_> {
+        BlockingMediaHandler { client: self }
+    }
+
+    /// Access notification-related endpoints.
+    pub fn notifications(&self) -> BlockingNotificationsHandler<'_> {
+        BlockingNotificationsHandler { client: self }
+    }
+
+    /// Access admin-related endpoints.
+    pub fn admin(&self) -> BlockingAdminHandler<'_> {
+        BlockingAdminHandler { client: self }
+    }
+}
+
+impl From<MastodonClient> for BlockingMastodonClient {
+    fn from(client: MastodonClient) -> Self {
+        Self::from_client(client)
+    }
+}
+
+/// Blocking counterpart of [`AccountsHandler`](crate::methods::accounts::AccountsHandler).
+pub struct BlockingAccountsHandler<'a> {
+    client: &'a BlockingMastodonClient,
+}
+
+impl BlockingAccountsHandler<'_> {
+    /// Registers a new account and returns a token for it. Requires an app token.
+    pub fn register(&self, params: &RegisterAccountParams) -> Result<Token> {
+        let client = self.client;
+        client.block_on(client.inner.accounts().register(params))
+    }
+
+    /// Verifies the credentials of the client and returns the authenticated account.
+    pub fn verify_credentials(&self) -> Result<Account> {
+        let client = self.client;
+        client.block_on(client.inner.accounts().verify_credentials())
+    }
+
+    /// Fetches an account by its ID.
+    pub fn get(&self, id: &str) -> Result<Account> {
+        let client = self.client;
+        client.block_on(client.inner.accounts().get(id))
+    }
+
+    /// Follows an account.
+    pub fn follow(&self, id: &str) -> Result<Relationship> {
+        let client = self.client;
+        client.block_on(client.inner.accounts().follow(id))
+    }
+
+    /// Unfollows an account.
+    pub fn unfollow(&self, id: &str) -> Result<Relationship> {
+        let client = self.client;
+        client.block_on(client.inner.accounts().unfollow(id))
+    }
+
+    /// Blocks an account.
+    pub fn block(&self, id: &str) -> Result<Relationship> {
+        let client = self.client;
+        client.block_on(client.inner.accounts().block(id))
+    }
+
+    /// Mutes an account.
+    pub fn mute(&self, id: &str) -> Result<Relationship> {
+        let client = self.client;
+        client.block_on(client.inner.accounts().mute(id))
+    }
+
+    /// Features an account on the profile of the authenticated user.
+    pub fn pin(&self, id: &str) -> Result<Relationship> {
+        let client = self.client;
+        client.block_on(client.inner.accounts().pin(id))
+    }
+
+    /// Removes an account from the profile of the authenticated user.
+    pub fn unpin(&self, id: &str) -> Result<Relationship> {
+        let client = self.client;
+        client.block_on(client.inner.accounts().unpin(id))
+    }
+
+    /// Searches for accounts matching the query.
+    pub fn search(&self, query: &str, limit: Option<u32>) -> Result<Vec<Account>> {
+        let client = self.client;
+        client.block_on(client.inner.accounts().search(query, limit))
+    }
+
+    /// Fetches the relationships of the authenticated user with the given accounts.
+    pub fn relationships(&self, ids: &[String]) -> Result<Vec<Relationship>> {
+        let client = self.client;
+        client.block_on(client.inner.accounts().relationships(ids))
+    }
+}
+
+/// Blocking counterpart of [`StatusesHandler`](crate::methods::statuses::StatusesHandler).
+pub struct BlockingStatusesHandler<'a> {
+    client: &'a BlockingMastodonClient,
+}
+
+impl BlockingStatusesHandler<'_> {
+    /// Fetches a status by its ID.
+    pub fn get(&self, id: &str) -> Result<Status> {
+        let client = self.client;
+        client.block_on(client.inner.statuses().get(id))
+    }
+
+    /// Creates a new status.
+    pub fn create(&self, params: &CreateStatusParams) -> Result<Status> {
+        let client = self.client;
+        client.block_on(client.inner.statuses().create(params))
+    }
+
+    /// Creates a new status using the given `Idempotency-Key`.
+    pub fn create_with_idempotency_key(
+        &self,
+        params: &CreateStatusParams,
+        key: &str,
+    ) -> Result<Status> {
+        let client = self.client;
+        client.block_on(
+            client
+                .inner
+                .statuses()
+                .create_with_idempotency_key(params, key),
+        )
+    }
+
+    /// Creates a new status with just text.
+    pub fn create_simple(&self, text: &str) -> Result<Status> {
+        let client = self.client;
+        client.block_on(client.inner.statuses().create_simple(text))
+    }
+
+    /// Deletes a status by its ID.
+    pub fn delete(&self, id: &str) -> Result<Status> {
+        let client = self.client;
+        client.block_on(client.inner.statuses().delete(id))
+    }
+
+    /// Reblogs (boosts) a status.
+    pub fn reblog(&self, id: &str) -> Result<Status> {
+        let client = self.client;
+        client.block_on(client.inner.statuses().reblog(id))
+    }
+
+    /// Undoes the reblog of a status.
+    pub fn unreblog(&self, id: &str) -> Result<Status> {
+        let client = self.client;
+        client.block_on(client.inner.statuses().unreblog(id))
+    }
+
+    /// Favourites a status.
+    pub fn favourite(&self, id: &str) -> Result<Status> {
+        let client = self.client;
+        client.block_on(client.inner.statuses().favourite(id))
+    }
+
+    /// Removes a status from the favourites.
+    pub fn unfavourite(&self, id: &str) -> Result<Status> {
+        let client = self.client;
+        client.block_on(client.inner.statuses().unfavourite(id))
+    }
+
+    /// Bookmarks a status.
+    pub fn bookmark(&self, id: &str) -> Result<Status> {
+        let client = self.client;
+        client.block_on(client.inner.statuses().bookmark(id))
+    }
+
+    /// Removes a status from the bookmarks.
+    pub fn unbookmark(&self, id: &str) -> Result<Status> {
+        let client = self.client;
+        client.block_on(client.inner.statuses().unbookmark(id))
+    }
+
+    /// Fetches the ancestors and descendants of a status.
+    pub fn get_context(&self, id: &str) -> Result<Context> {
+        let client = self.client;
+        client.block_on(client.inner.statuses().get_context(id))
+    }
+}
+
+/// Blocking counterpart of [`TimelinesHandler`](crate::methods::timelines::TimelinesHandler).
+pub struct BlockingTimelinesHandler<'a> {
+    client: &'a BlockingMastodonClient,
+}
+
+impl BlockingTimelinesHandler<'_> {
+    /// Fetches the public timeline.
+    pub fn public(&self) -> Result<Vec<Status>> {
+        let client = self.client;
+        client.block_on(client.inner.timelines().public())
+    }
+
+    /// Fetches the public timeline along with the response metadata.
+    pub fn public_with_meta(&self) -> Result<Response<Vec<Status>>> {
+        let client = self.client;
+        client.block_on(client.inner.timelines().public_with_meta())
+    }
+
+    /// Fetches the home timeline for the authenticated user.
+    pub fn home(&self) -> Result<Vec<Status>> {
+        let client = self.client;
+        client.block_on(client.inner.timelines().home())
+    }
+
+    /// Fetches the home timeline along with the response metadata.
+    pub fn home_with_meta(&self) -> Result<Response<Vec<Status>>> {
+        let client = self.client;
+        client.block_on(client.inner.timelines().home_with_meta())
+    }
+}
+
+/// Blocking counterpart of [`MediaHandler`](crate::methods::media::MediaHandler).
+pub struct BlockingMediaHandler<'a> {
+    client: &'a BlockingMastodonClient,
+}
+
+impl BlockingMediaHandler<'_> {
+    /// Uploads a media file (image, video, etc.) to the instance.
+    pub fn upload(&self, file_path: &str, description: Option<String>) -> Result<MediaAttachment> {
+        let client = self.client;
+        client.block_on(client.inner.media().upload(file_path, description))
+    }
+}
+
+/// Blocking counterpart of [`NotificationsHandler`](crate::methods::notifications::NotificationsHandler).
+pub struct BlockingNotificationsHandler<'a> {
+    client: &'a BlockingMastodonClient,
+}
+
+impl BlockingNotificationsHandler<'_> {
+    /// Fetches all notifications for the authenticated user.
+    pub fn list(&self) -> Result<Vec<Notification>> {
+        let client = self.client;
+        client.block_on(client.inner.notifications().list())
+    }
+
+    /// Fetches all notifications along with the response metadata.
+    pub fn list_with_meta(&self) -> Result<Response<Vec<Notification>>> {
+        let client = self.client;
+        client.block_on(client.inner.notifications().list_with_meta())
+    }
+
+    /// Fetches a notification by its ID.
+    pub fn get(&self, id: &str) -> Result<Notification> {
+        let client = self.client;
+        client.block_on(client.inner.notifications().get(id))
+    }
+
+    /// Clears all notifications for the authenticated user.
+    pub fn clear(&self) -> Result<()> {
+        let client = self.client;
+        client.block_on(client.inner.notifications().clear())
+    }
+
+    /// Dismisses a single notification.
+    pub fn dismiss(&self, id: &str) -> Result<()> {
+        let client = self.client;
+        client.block_on(client.inner.notifications().dismiss(id))
+    }
+}
+
+/// Blocking counterpart of [`AdminHandler`](crate::methods::admin::AdminHandler).
+pub struct BlockingAdminHandler<'a> {
+    client: &'a BlockingMastodonClient,
+}
+
+impl<'a> BlockingAdminHandler<'a> {
+    /// Access admin account-related endpoints.
+    pub fn accounts(&self) -> BlockingAdminAccountsHandler<'a> {
+        BlockingAdminAccountsHandler {
+            client: self.client,
+        }
+    }
+
+    /// Access admin report-related endpoints.
+    pub fn reports(&self) -> BlockingAdminReportsHandler<'a> {
+        BlockingAdminReportsHandler {
+            client: self.client,
+        }
+    }
+
+    /// Access admin domain federation-related endpoints.
+    pub fn domain_federation(&self) -> BlockingAdminDomainFederationHandler<'a> {
+        BlockingAdminDomainFederationHandler {
+            client: self.client,
+        }
+    }
+
+    /// Access admin IP block-related endpoints.
+    pub fn ip_blocks(&self) -> BlockingAdminIpBlocksHandler<'a> {
+        BlockingAdminIpBlocksHandler {
+            client: self.client,
+        }
+    }
+}
+
+/// Blocking counterpart of [`AdminAccountsHandler`](crate::methods::admin::accounts::AdminAccountsHandler).
+pub struct BlockingAdminAccountsHandler<'a> {
+    client: &'a BlockingMastodonClient,
+}
+
+impl BlockingAdminAccountsHandler<'_> {
+    /// Performs a moderation action (e.g. `suspend`, `silence`) on an account.
+    pub fn action(&self, id: &str, r#type: &str) -> Result<()> {
+        let client = self.client;
+        client.block_on(client.inner.admin().accounts().action(id, r#type))
+    }
+
+    /// Suspends an account.
+    pub fn suspend(&self, id: &str) -> Result<()> {
+        let client = self.client;
+        client.block_on(client.inner.admin().accounts().suspend(id))
+    }
+
+    /// Silences (limits) an account.
+    pub fn silence(&self, id: &str) -> Result<()> {
+        let client = self.client;
+        client.block_on(client.inner.admin().accounts().silence(id))
+    }
+}
+
+/// Blocking counterpart of [`AdminReportsHandler`](crate::methods::admin::reports::AdminReportsHandler).
+pub struct BlockingAdminReportsHandler<'a> {
+    client: &'a BlockingMastodonClient,
+}
+
+impl BlockingAdminReportsHandler<'_> {
+    /// Fetches the reports filed on the instance.
+    pub fn list(&self) -> Result<serde_json::Value> {
+        let client = self.client;
+        client.block_on(client.inner.admin().reports().list())
+    }
+
+    /// Marks a report as resolved.
+    pub fn resolve(&self, id: &str) -> Result<()> {
+        let client = self.client;
+        client.block_on(client.inner.admin().reports().resolve(id))
+    }
+}
+
+/// Blocking counterpart of [`AdminDomainFederationHandler`](crate::methods::admin::domain_federation::AdminDomainFederationHandler).
+pub struct BlockingAdminDomainFederationHandler<'a> {
+    client: &'a BlockingMastodonClient,
+}
+
+impl BlockingAdminDomainFederationHandler<'_> {
+    /// Fetches the domains explicitly allowed to federate.
+    pub fn list_allows(&self) -> Result<serde_json::Value> {
+        let client = self.client;
+        client.block_on(client.inner.admin().domain_federation().list_allows())
+    }
+
+    /// Fetches the domains blocked from federating.
+    pub fn list_blocks(&self) -> Result<serde_json::Value> {
+        let client = self.client;
+        client.block_on(client.inner.admin().domain_federation().list_blocks())
+    }
+}
+
+/// Blocking counterpart of [`AdminIpBlocksHandler`](crate::methods::admin::ip_blocks::AdminIpBlocksHandler).
+pub struct BlockingAdminIpBlocksHandler<'a> {
+    client: &'a BlockingMastodonClient,
+}
+
+impl BlockingAdminIpBlocksHandler<'_> {
+    /// Fetches the blocked IP ranges.
+    pub fn list(&self) -> Result<serde_json::Value> {
+        let client = self.client;
+        client.block_on(client.inner.admin().ip_blocks().list())
+    }
+}
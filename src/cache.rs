@@ -0,0 +1,212 @@
+//! Conditional request caching for read endpoints.
+//!
+//! A [`ResponseCache`] keeps the bodies of successful `GET` responses together with
+//! their `ETag` and `Last-Modified` validators. Later requests to the same URL are
+//! sent with `If-None-Match` / `If-Modified-Since`, and a `304 Not Modified` answer
+//! is served from the cache. Endpoints can additionally be given a time to live,
+//! during which cached responses are returned without contacting the server at all.
+
+use crate::response::HttpResponse;
+use reqwest::StatusCode;
+use reqwest::header::{
+    AUTHORIZATION, ETAG, HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// An opt-in cache for `GET` responses.
+///
+/// Cloning a `ResponseCache` is cheap and all clones share the same entries. Entries
+/// are keyed by URL and access token, so clients authenticated as different accounts
+/// never see each other's responses.
+///
+/// # Example
+/// ```
+/// use mastodon_api::{MastodonClient, ResponseCache};
+/// use std::time::Duration;
+///
+/// let cache = ResponseCache::new()
+///     .ttl("/api/v1/instance", Duration::from_secs(3600))
+///     .ttl("/api/v1/custom_emojis", Duration::from_secs(600));
+/// let client = MastodonClient::new("https://mastodon.social").with_cache(cache.clone());
+///
+/// // Forget the cached instance information, e.g. after changing server settings.
+/// cache.invalidate("/api/v1/instance");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+    ttls: HashMap<String, Duration>,
+    default_ttl: Option<Duration>,
+    max_entries: usize,
+}
+
+#[derive(Debug)]
+struct Entry {
+    path: String,
+    response: HttpResponse,
+    stored_at: Instant,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResponseCache {
+    /// Creates a new `ResponseCache` that revalidates every request and holds up to
+    /// 256 responses.
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            ttls: HashMap::new(),
+            default_ttl: None,
+            max_entries: 256,
+        }
+    }
+
+    /// Returns an empty cache with the same settings that does not share its entries.
+    pub(crate) fn unshared(&self) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            ..self.clone()
+        }
+    }
+
+    /// Time during which responses of the endpoint with the given path are served
+    /// from the cache without revalidation, e.g. `/api/v1/instance`.
+    pub fn ttl(mut self, path: &str, value: Duration) -> Self {
+        self.ttls.insert(path.to_string(), value);
+        self
+    }
+
+    /// Time to live for endpoints without their own [`ttl`](Self::ttl).
+    pub fn default_ttl(mut self, value: Duration) -> Self {
+        self.default_ttl = Some(value);
+        self
+    }
+
+    /// Maximum number of cached responses. The oldest entry is evicted when it is exceeded.
+    pub fn max_entries(mut self, value: usize) -> Self {
+        self.max_entries = value.max(1);
+        self
+    }
+
+    /// Removes all cached responses of the endpoint with the given path.
+    pub fn invalidate(&self, path: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|_, entry| entry.path != path);
+    }
+
+    /// Removes all cached responses.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Returns the number of cached responses.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Returns `true` if no responses are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the key of the entry for the request: its URL, followed by a digest of
+    /// its `Authorization` header so the token itself is never kept in a key.
+    pub(crate) fn key(request: &reqwest::Request) -> String {
+        match request.headers().get(AUTHORIZATION) {
+            Some(authorization) => format!(
+                "{} {:x}",
+                request.url(),
+                Sha256::digest(authorization.as_bytes())
+            ),
+            None => request.url().to_string(),
+        }
+    }
+
+    /// Returns the cached response if it is still within its time to live; otherwise
+    /// adds the validators of the cached response to the request.
+    pub(crate) fn lookup(&self, key: &str, request: &mut reqwest::Request) -> Option<HttpResponse> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(key)?;
+
+        if let Some(ttl) = self.ttl_for(&entry.path)
+            && entry.stored_at.elapsed() < ttl
+        {
+            return Some(entry.response.clone());
+        }
+
+        let headers = request.headers_mut();
+        if let Some(etag) = entry.response.headers.get(ETAG) {
+            headers.insert(IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(last_modified) = entry.response.headers.get(LAST_MODIFIED) {
+            headers.insert(IF_MODIFIED_SINCE, last_modified.clone());
+        }
+        None
+    }
+
+    /// Stores a successful response, or resolves a `304 Not Modified` to the cached one.
+    ///
+    /// Returns `None` for a `304 Not Modified` whose entry was evicted or invalidated
+    /// while the request was in flight.
+    pub(crate) fn store(&self, key: &str, response: HttpResponse) -> Option<HttpResponse> {
+        let mut entries = self.entries.lock().unwrap();
+
+        if response.status == StatusCode::NOT_MODIFIED {
+            let entry = entries.get_mut(key)?;
+            entry.stored_at = Instant::now();
+            refresh_headers(&mut entry.response.headers, &response.headers);
+            return Some(entry.response.clone());
+        }
+
+        let path = response.url.path().to_string();
+        let cacheable = response.status == StatusCode::OK
+            && (response.headers.contains_key(ETAG)
+                || response.headers.contains_key(LAST_MODIFIED)
+                || self.ttl_for(&path).is_some());
+        if !cacheable {
+            return Some(response);
+        }
+
+        if entries.len() >= self.max_entries
+            && !entries.contains_key(key)
+            && let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone())
+        {
+            entries.remove(&oldest);
+        }
+        entries.insert(
+            key.to_string(),
+            Entry {
+                path,
+                response: response.clone(),
+                stored_at: Instant::now(),
+            },
+        );
+        Some(response)
+    }
+
+    fn ttl_for(&self, path: &str) -> Option<Duration> {
+        self.ttls.get(path).copied().or(self.default_ttl)
+    }
+}
+
+/// Applies the headers of a `304 Not Modified` response to the cached response,
+/// so validators and rate limit information stay current.
+fn refresh_headers(cached: &mut HeaderMap, fresh: &HeaderMap) {
+    for (name, value) in fresh {
+        if name != reqwest::header::CONTENT_LENGTH {
+            cached.insert(name, value.clone());
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Detection of the API features an instance supports.
+//!
+//! Instances run different Mastodon versions and forks, and older versions lack
+//! endpoints this crate knows about. [`MastodonClient::detect_capabilities`](crate::MastodonClient::detect_capabilities)
+//! reads the server version from the instance metadata; afterwards, handlers for
+//! endpoints the instance does not support fail with [`MastodonError::Unsupported`]
+//! before sending anything, instead of with an opaque 404.
+//!
+//! Until capabilities have been detected, all features are assumed to be available.
+
+use crate::error::{MastodonError, Result};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A version of Mastodon, as reported by an instance.
+///
+/// Forks append suffixes (`4.2.8+glitch`, `4.3.0-beta.1`), and other server software
+/// reports the Mastodon version it is compatible with
+/// (`2.7.2 (compatible; Pleroma 2.5.0)`). Both are kept in [`suffix`](Self::suffix).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerVersion {
+    /// Major version number.
+    pub major: u32,
+    /// Minor version number.
+    pub minor: u32,
+    /// Patch version number.
+    pub patch: u32,
+    /// Everything after the version numbers, e.g. `+glitch` or `(compatible; Pleroma 2.5.0)`.
+    pub suffix: Option<String>,
+}
+
+impl ServerVersion {
+    /// Parses a version string such as `4.2.1`, `4.3.0-beta.1` or `3.5.3+glitch`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let end = value
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(value.len());
+        let mut numbers = value[..end].split('.').filter(|part| !part.is_empty());
+        let major = numbers.next()?.parse().ok()?;
+        let minor = numbers.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+        let patch = numbers.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+        let suffix = value[end..].trim();
+        Some(Self {
+            major,
+            minor,
+            patch,
+            suffix: (!suffix.is_empty()).then(|| suffix.to_string()),
+        })
+    }
+
+    /// Returns whether this version is at least `major.minor.patch`.
+    ///
+    /// Pre-releases count as the version they lead up to.
+    pub fn at_least(&self, major: u32, minor: u32, patch: u32) -> bool {
+        (self.major, self.minor, self.patch) >= (major, minor, patch)
+    }
+}
+
+impl fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        match &self.suffix {
+            Some(suffix) if suffix.starts_with('(') => write!(f, " {}", suffix),
+            Some(suffix) => write!(f, "{}", suffix),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An API feature that is not available on every instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Feature {
+    /// `GET /api/v2/search`, added in Mastodon 2.4.1.
+    SearchV2,
+    /// `/api/v1/featured_tags`, added in Mastodon 3.0.0.
+    FeaturedTags,
+    /// `GET /api/v1/featured_tags/suggestions`, added in Mastodon 3.0.0.
+    FeaturedTagSuggestions,
+    /// `GET /api/v1/trends/statuses`, added in Mastodon 3.5.0.
+    TrendingStatuses,
+    /// `/api/v1/followed_tags` and following tags, added in Mastodon 4.0.0.
+    FollowedTags,
+}
+
+impl Feature {
+    /// Returns the first Mastodon version supporting the feature.
+    pub fn min_version(&self) -> (u32, u32, u32) {
+        match self {
+            Feature::SearchV2 => (2, 4, 1),
+            Feature::FeaturedTags | Feature::FeaturedTagSuggestions => (3, 0, 0),
+            Feature::TrendingStatuses => (3, 5, 0),
+            Feature::FollowedTags => (4, 0, 0),
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Feature::SearchV2 => "v2 search",
+            Feature::FeaturedTags => "featured tags",
+            Feature::FeaturedTagSuggestions => "featured tag suggestions",
+            Feature::TrendingStatuses => "trending statuses",
+            Feature::FollowedTags => "followed tags",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The features supported by an instance.
+///
+/// # Example
+/// ```
+/// use mastodon_api::capabilities::{Capabilities, Feature, ServerVersion};
+///
+/// let mut capabilities = Capabilities::from_version(ServerVersion::parse("3.5.3+glitch").unwrap());
+/// assert!(capabilities.supports(Feature::TrendingStatuses));
+/// assert!(!capabilities.supports(Feature::FollowedTags));
+///
+/// // The fork backported followed tags.
+/// capabilities.set(Feature::FollowedTags, true);
+/// assert!(capabilities.supports(Feature::FollowedTags));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Capabilities {
+    version: Option<ServerVersion>,
+    overrides: HashMap<Feature, bool>,
+}
+
+impl Capabilities {
+    /// Capabilities of an instance running the given version.
+    pub fn from_version(version: ServerVersion) -> Self {
+        Self {
+            version: Some(version),
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Returns the detected server version, if known.
+    pub fn version(&self) -> Option<&ServerVersion> {
+        self.version.as_ref()
+    }
+
+    /// Returns whether the instance supports the feature.
+    ///
+    /// Features are assumed to be supported if the version is unknown.
+    pub fn supports(&self, feature: Feature) -> bool {
+        if let Some(enabled) = self.overrides.get(&feature) {
+            return *enabled;
+        }
+        let (major, minor, patch) = feature.min_version();
+        self.version
+            .as_ref()
+            .is_none_or(|version| version.at_least(major, minor, patch))
+    }
+
+    /// Marks a feature as supported or unsupported, regardless of the version.
+    pub fn set(&mut self, feature: Feature, enabled: bool) {
+        self.overrides.insert(feature, enabled);
+    }
+
+    /// Removes a manual override, so support is derived from the version again.
+    pub fn reset(&mut self, feature: Feature) {
+        self.overrides.remove(&feature);
+    }
+
+    /// Replaces the version, keeping manual overrides.
+    pub(crate) fn set_version(&mut self, version: Option<ServerVersion>) {
+        self.version = version;
+    }
+
+    /// Fails with [`MastodonError::Unsupported`] if the feature is not supported.
+    pub(crate) fn require(&self, feature: Feature) -> Result<()> {
+        if self.supports(feature) {
+            return Ok(());
+        }
+        Err(MastodonError::Unsupported {
+            feature,
+            version: self
+                .version
+                .as_ref()
+                .map_or_else(|| "unknown".to_string(), ToString::to_string),
+        })
+    }
+}
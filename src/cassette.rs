@@ -0,0 +1,363 @@
+//! Record/replay of HTTP interactions for deterministic tests.
+//!
+//! A [`CassetteTransport`] in record mode forwards requests to another transport
+//! and collects every request/response pair, writing them to a JSON cassette file
+//! when it is saved or dropped. In replay mode
+//! it serves the recorded responses without touching the network, so tests of code
+//! built on [`MastodonClient`](crate::MastodonClient) run offline and always see the
+//! same data.
+//!
+//! Credentials are scrubbed before anything is written: the `Authorization` header,
+//! cookies, `access_token` query parameters and token or secret fields in bodies.
+
+use crate::error::{MastodonError, Result};
+use crate::response::HttpResponse;
+use crate::transport::Transport;
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use url::Url;
+
+/// Replaces scrubbed credentials in a cassette.
+const FILTERED: &str = "[FILTERED]";
+
+/// Headers that are never written to a cassette.
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "cookie",
+    "set-cookie",
+    "proxy-authorization",
+];
+
+/// Query parameters and request body fields holding credentials.
+const SENSITIVE_FIELDS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "client_secret",
+    "code",
+    "code_verifier",
+    "password",
+];
+
+/// Response body fields holding credentials, e.g. in OAuth token responses.
+const SENSITIVE_RESPONSE_FIELDS: &[&str] = &["access_token", "refresh_token", "client_secret"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    /// Path and query of the request; the host is not recorded, so a cassette can be
+    /// replayed against any base URL.
+    uri: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+enum Mode {
+    /// The inner transport, and whether interactions were recorded since the last save.
+    Record(Box<dyn Transport>, AtomicBool),
+    Replay(Mutex<Vec<bool>>),
+}
+
+/// A transport recording interactions to, or replaying them from, a cassette file.
+///
+/// # Example
+/// ```no_run
+/// use mastodon_api::MastodonClient;
+/// use mastodon_api::cassette::CassetteTransport;
+/// use mastodon_api::transport::ReqwestTransport;
+/// use std::sync::Arc;
+///
+/// # #[tokio::main]
+/// # async fn main() -> mastodon_api::Result<()> {
+/// // Run once against a real instance to create the cassette...
+/// let recorder = Arc::new(CassetteTransport::record(
+///     "tests/cassettes/home.json",
+///     ReqwestTransport::new(reqwest::Client::new()),
+/// ));
+/// let client = MastodonClient::new("https://staging.example")
+///     .with_token("your_token")
+///     .with_transport(recorder.clone());
+/// client.timelines().home().await?;
+/// recorder.save()?;
+///
+/// // ...then replay it in tests, without network access.
+/// let player = CassetteTransport::replay("tests/cassettes/home.json")?;
+/// let client = MastodonClient::new("https://staging.example").with_transport(player);
+/// let home = client.timelines().home().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct CassetteTransport {
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+    mode: Mode,
+}
+
+impl CassetteTransport {
+    /// Forwards requests to `inner` and records them for the cassette at `path`.
+    ///
+    /// The recording replaces any earlier one once [`save`](Self::save) is called or
+    /// the transport is dropped.
+    pub fn record(path: impl AsRef<Path>, inner: impl Transport + 'static) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            cassette: Mutex::new(Cassette::default()),
+            mode: Mode::Record(Box::new(inner), AtomicBool::new(false)),
+        }
+    }
+
+    /// Serves the responses recorded in the cassette at `path`.
+    ///
+    /// Each recorded interaction is served once, matched by method, path, query and
+    /// body. Requests without a matching interaction fail with [`MastodonError::Custom`].
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let data = std::fs::read(&path).map_err(|err| {
+            MastodonError::Custom(format!("Cassette {}: {}", path.display(), err))
+        })?;
+        let cassette: Cassette = serde_json::from_slice(&data)?;
+        let used = vec![false; cassette.interactions.len()];
+        Ok(Self {
+            path,
+            cassette: Mutex::new(cassette),
+            mode: Mode::Replay(Mutex::new(used)),
+        })
+    }
+
+    /// Returns the number of recorded interactions that have not been replayed yet.
+    pub fn remaining(&self) -> usize {
+        match &self.mode {
+            Mode::Record(..) => 0,
+            Mode::Replay(used) => used.lock().unwrap().iter().filter(|used| !**used).count(),
+        }
+    }
+
+    /// Writes the interactions recorded so far to the cassette file.
+    ///
+    /// This blocks on file IO, so call it once recording is done rather than between
+    /// requests. It does nothing in replay mode.
+    pub fn save(&self) -> Result<()> {
+        let Mode::Record(_, unsaved) = &self.mode else {
+            return Ok(());
+        };
+        let cassette = self.cassette.lock().unwrap().clone();
+        unsaved.store(false, Ordering::SeqCst);
+        self.write(&cassette)
+            .inspect_err(|_| unsaved.store(true, Ordering::SeqCst))
+    }
+
+    fn write(&self, cassette: &Cassette) -> Result<()> {
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .map_err(|err| MastodonError::Custom(format!("Cassette directory: {}", err)))?;
+        }
+        std::fs::write(&self.path, serde_json::to_vec_pretty(cassette)?).map_err(|err| {
+            MastodonError::Custom(format!("Cassette {}: {}", self.path.display(), err))
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for CassetteTransport {
+    async fn send(&self, request: reqwest::Request) -> Result<HttpResponse> {
+        let recorded_request = RecordedRequest {
+            method: request.method().to_string(),
+            uri: scrub_uri(request.url()),
+            headers: scrub_headers(request.headers()),
+            body: scrub_body(
+                request
+                    .body()
+                    .and_then(|body| body.as_bytes())
+                    .unwrap_or_default(),
+                SENSITIVE_FIELDS,
+            ),
+        };
+
+        match &self.mode {
+            Mode::Record(inner, unsaved) => {
+                let response = inner.send(request).await?;
+                let interaction = Interaction {
+                    request: recorded_request,
+                    response: RecordedResponse {
+                        status: response.status.as_u16(),
+                        headers: scrub_headers(&response.headers),
+                        body: scrub_body(&response.body, SENSITIVE_RESPONSE_FIELDS),
+                    },
+                };
+                self.cassette.lock().unwrap().interactions.push(interaction);
+                unsaved.store(true, Ordering::SeqCst);
+                Ok(response)
+            }
+            Mode::Replay(used) => {
+                let cassette = self.cassette.lock().unwrap();
+                let mut used = used.lock().unwrap();
+                let index = cassette
+                    .interactions
+                    .iter()
+                    .zip(used.iter())
+                    .position(|(interaction, used)| {
+                        !used
+                            && interaction.request.method == recorded_request.method
+                            && interaction.request.uri == recorded_request.uri
+                            && interaction.request.body == recorded_request.body
+                    })
+                    .ok_or_else(|| {
+                        MastodonError::Custom(format!(
+                            "No recorded interaction for {} {} in cassette {}",
+                            recorded_request.method,
+                            recorded_request.uri,
+                            self.path.display()
+                        ))
+                    })?;
+                used[index] = true;
+
+                let recorded = &cassette.interactions[index].response;
+                Ok(HttpResponse {
+                    method: request.method().clone(),
+                    url: request.url().clone(),
+                    status: StatusCode::from_u16(recorded.status)
+                        .map_err(|err| MastodonError::Custom(err.to_string()))?,
+                    headers: restore_headers(&recorded.headers),
+                    body: recorded.body.clone().into_bytes(),
+                })
+            }
+        }
+    }
+}
+
+/// Returns the path and query of the URL, with credentials in the query replaced.
+fn scrub_uri(url: &Url) -> String {
+    let mut uri = url.path().to_string();
+    if url.query().is_some() {
+        let query: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(name, value)| {
+                let value = if SENSITIVE_FIELDS.contains(&name.as_ref()) {
+                    FILTERED.into()
+                } else {
+                    value.into_owned()
+                };
+                (name.into_owned(), value)
+            })
+            .collect();
+        uri.push('?');
+        uri.push_str(
+            &url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(query)
+                .finish(),
+        );
+    }
+    uri
+}
+
+fn scrub_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter(|(name, _)| !SENSITIVE_HEADERS.contains(&name.as_str()))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+fn restore_headers(headers: &[(String, String)]) -> HeaderMap {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            map.append(name, value);
+        }
+    }
+    map
+}
+
+/// Replaces the given fields in JSON and form-encoded bodies.
+fn scrub_body(body: &[u8], fields: &[&str]) -> String {
+    if let Ok(mut json) = serde_json::from_slice::<serde_json::Value>(body) {
+        scrub_json(&mut json, fields);
+        return json.to_string();
+    }
+    let text = String::from_utf8_lossy(body);
+    let is_form = !text.is_empty()
+        && url::form_urlencoded::parse(text.as_bytes())
+            .any(|(name, _)| fields.contains(&name.as_ref()));
+    if !is_form {
+        return text.into_owned();
+    }
+    url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(
+            url::form_urlencoded::parse(text.as_bytes()).map(|(name, value)| {
+                let value = if fields.contains(&name.as_ref()) {
+                    FILTERED.into()
+                } else {
+                    value
+                };
+                (name, value)
+            }),
+        )
+        .finish()
+}
+
+fn scrub_json(value: &mut serde_json::Value, fields: &[&str]) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (name, value) in map.iter_mut() {
+                if fields.contains(&name.as_str()) && value.is_string() {
+                    *value = FILTERED.into();
+                } else {
+                    scrub_json(value, fields);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| scrub_json(value, fields)),
+        _ => {}
+    }
+}
+
+impl Drop for CassetteTransport {
+    fn drop(&mut self) {
+        if let Mode::Record(_, unsaved) = &self.mode
+            && unsaved.load(Ordering::SeqCst)
+        {
+            // Errors cannot be reported from here; call `save` to handle them.
+            let _ = self.save();
+        }
+    }
+}
+
+impl std::fmt::Debug for CassetteTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mode = match self.mode {
+            Mode::Record(..) => "record",
+            Mode::Replay(_) => "replay",
+        };
+        f.debug_struct("CassetteTransport")
+            .field("path", &self.path)
+            .field("mode", &mode)
+            .finish()
+    }
+}
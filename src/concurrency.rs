@@ -0,0 +1,89 @@
+//! Limits on the number of requests in flight at the same time.
+//!
+//! A [`ConcurrencyLimiter`] caps how many requests a client sends in parallel, in
+//! total and per host. Requests over the limit wait for a free slot instead of
+//! failing, so fanning out with `join_all` cannot overwhelm a small instance.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Caps the number of concurrent requests, globally and per host.
+///
+/// Cloning a `ConcurrencyLimiter` is cheap and all clones share the same slots, so
+/// one limiter covers every clone of a [`MastodonClient`](crate::MastodonClient)
+/// and can be handed to several clients to share a limit between them.
+///
+/// # Example
+/// ```
+/// use mastodon_api::{ConcurrencyLimiter, MastodonClient};
+///
+/// let limiter = ConcurrencyLimiter::new().max_in_flight(16).max_per_host(4);
+/// let client = MastodonClient::new("https://mastodon.social").with_concurrency_limiter(limiter);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConcurrencyLimiter {
+    global: Option<Arc<Semaphore>>,
+    max_per_host: Option<usize>,
+    hosts: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+}
+
+/// A slot for one request, released when dropped.
+pub(crate) struct Permit {
+    _global: Option<OwnedSemaphorePermit>,
+    _host: Option<OwnedSemaphorePermit>,
+}
+
+impl ConcurrencyLimiter {
+    /// Creates a new `ConcurrencyLimiter` without any limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of requests in flight across all hosts.
+    pub fn max_in_flight(mut self, value: usize) -> Self {
+        self.global = Some(Arc::new(Semaphore::new(value.max(1))));
+        self
+    }
+
+    /// Maximum number of requests in flight to a single host.
+    pub fn max_per_host(mut self, value: usize) -> Self {
+        self.max_per_host = Some(value.max(1));
+        self.hosts = Arc::new(Mutex::new(HashMap::new()));
+        self
+    }
+
+    /// Returns the number of requests that can currently start without waiting
+    /// for the global limit, or `None` if there is no global limit.
+    pub fn available(&self) -> Option<usize> {
+        self.global
+            .as_ref()
+            .map(|semaphore| semaphore.available_permits())
+    }
+
+    /// Waits until a request to `host` may be sent.
+    pub(crate) async fn acquire(&self, host: &str) -> Permit {
+        // Take the host slot first, so requests queued for a busy host don't hold
+        // global slots that requests to other hosts could use.
+        let host_semaphore = self.max_per_host.map(|max| {
+            self.hosts
+                .lock()
+                .unwrap()
+                .entry(host.to_string())
+                .or_insert_with(|| Arc::new(Semaphore::new(max)))
+                .clone()
+        });
+        let host_permit = match host_semaphore {
+            Some(semaphore) => semaphore.acquire_owned().await.ok(),
+            None => None,
+        };
+        let global_permit = match &self.global {
+            Some(semaphore) => semaphore.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+        Permit {
+            _global: global_permit,
+            _host: host_permit,
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! Persistence of app registrations and access tokens.
+//!
+//! A [`CredentialStore`] keeps one [`AppRegistration`] per instance and one [`Token`]
+//! per account, so bots do not have to register a new app or ask the user to log in
+//! on every start. [`JsonFileStore`] keeps them in a JSON file; other backends, such
+//! as the system keyring, can implement the trait.
+//!
+//! [`MastodonClient::from_store`] and [`MastodonClient::load_or_register_app`] build
+//! on a store.
+
+use crate::error::{MastodonError, Result};
+use crate::methods::apps::AppRegistration;
+use crate::oauth::Token;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Storage for app registrations and access tokens.
+///
+/// Instances are identified by their base URL, as returned by
+/// [`MastodonClient::base_url`](crate::MastodonClient::base_url). Accounts are
+/// identified by a name chosen by the application, e.g. `@bot@mastodon.social`.
+#[async_trait]
+pub trait CredentialStore: Send + Sync {
+    /// Returns the app registered with the instance, if any.
+    async fn load_app(&self, instance: &str) -> Result<Option<AppRegistration>>;
+
+    /// Stores the app registered with the instance, replacing any earlier one.
+    async fn save_app(&self, instance: &str, app: &AppRegistration) -> Result<()>;
+
+    /// Returns the token of the account on the instance, if any.
+    async fn load_token(&self, instance: &str, account: &str) -> Result<Option<Token>>;
+
+    /// Stores the token of the account on the instance, replacing any earlier one.
+    async fn save_token(&self, instance: &str, account: &str, token: &Token) -> Result<()>;
+
+    /// Removes the token of the account on the instance, e.g. after revoking it.
+    async fn remove_token(&self, instance: &str, account: &str) -> Result<()>;
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+struct State {
+    #[serde(default)]
+    apps: BTreeMap<String, AppRegistration>,
+    #[serde(default)]
+    tokens: BTreeMap<String, BTreeMap<String, Token>>,
+}
+
+/// A [`CredentialStore`] backed by a JSON file.
+///
+/// The file is rewritten on every change. On Unix, it is only readable by its owner.
+///
+/// # Example
+/// ```no_run
+/// use mastodon_api::MastodonClient;
+/// use mastodon_api::credentials::JsonFileStore;
+///
+/// # #[tokio::main]
+/// # async fn main() -> mastodon_api::Result<()> {
+/// let store = JsonFileStore::open("credentials.json")?;
+/// let client = MastodonClient::from_store("https://mastodon.social", &store, "@bot").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct JsonFileStore {
+    path: PathBuf,
+    state: Mutex<State>,
+}
+
+impl JsonFileStore {
+    /// Opens the store at `path`, starting empty if the file does not exist yet.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let state = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => State::default(),
+            Err(err) => return Err(io_error(&path, err)),
+        };
+        Ok(Self {
+            path,
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Returns the instances with a stored app registration.
+    pub fn instances(&self) -> Vec<String> {
+        self.state.lock().unwrap().apps.keys().cloned().collect()
+    }
+
+    /// Returns the accounts with a stored token on the instance.
+    pub fn accounts(&self, instance: &str) -> Vec<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .tokens
+            .get(instance)
+            .map(|tokens| tokens.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Applies a change, keeping the previous state if it cannot be saved.
+    fn update(&self, change: impl FnOnce(&mut State)) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let mut changed = state.clone();
+        change(&mut changed);
+        self.save(&changed)?;
+        *state = changed;
+        Ok(())
+    }
+
+    fn save(&self, state: &State) -> Result<()> {
+        let data = serde_json::to_vec_pretty(state)?;
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        write_private(Path::new(&tmp), &data).map_err(|err| io_error(&self.path, err))?;
+        std::fs::rename(&tmp, &self.path).map_err(|err| io_error(&self.path, err))
+    }
+}
+
+#[async_trait]
+impl CredentialStore for JsonFileStore {
+    async fn load_app(&self, instance: &str) -> Result<Option<AppRegistration>> {
+        Ok(self.state.lock().unwrap().apps.get(instance).cloned())
+    }
+
+    async fn save_app(&self, instance: &str, app: &AppRegistration) -> Result<()> {
+        self.update(|state| {
+            state.apps.insert(instance.to_string(), app.clone());
+        })
+    }
+
+    async fn load_token(&self, instance: &str, account: &str) -> Result<Option<Token>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .tokens
+            .get(instance)
+            .and_then(|tokens| tokens.get(account))
+            .cloned())
+    }
+
+    async fn save_token(&self, instance: &str, account: &str, token: &Token) -> Result<()> {
+        self.update(|state| {
+            state
+                .tokens
+                .entry(instance.to_string())
+                .or_default()
+                .insert(account.to_string(), token.clone());
+        })
+    }
+
+    async fn remove_token(&self, instance: &str, account: &str) -> Result<()> {
+        self.update(|state| {
+            if let Some(tokens) = state.tokens.get_mut(instance) {
+                tokens.remove(account);
+                if tokens.is_empty() {
+                    state.tokens.remove(instance);
+                }
+            }
+        })
+    }
+}
+
+impl std::fmt::Debug for JsonFileStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonFileStore")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    // The mode only applies to new files, so a leftover file must not be reused.
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(data)
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, data)
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> MastodonError {
+    MastodonError::Custom(format!("Credential store {}: {}", path.display(), err))
+}
@@ -0,0 +1,105 @@
+//! Dry-run mode, for trying out bots against a live instance without changing anything.
+//!
+//! A client in dry-run mode sends read-only requests (`GET`, `HEAD`, `OPTIONS`) as
+//! usual, but records every other request in a [`DryRunLog`] instead of sending it.
+//! Endpoints without a meaningful response, such as `notifications().clear()`,
+//! succeed; all others fail with [`MastodonError::DryRun`].
+
+use crate::error::MastodonError;
+use reqwest::Method;
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+/// A request that would have been sent if dry-run mode were off.
+#[derive(Debug, Clone)]
+pub struct PlannedRequest {
+    /// Method of the request.
+    pub method: Method,
+    /// Full URL of the request, including the query string.
+    pub url: Url,
+    /// Body of the request; empty for streaming bodies such as file uploads.
+    pub body: Vec<u8>,
+}
+
+impl PlannedRequest {
+    /// Returns the body as text, replacing invalid UTF-8 sequences.
+    pub fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// The requests intercepted by a client in dry-run mode.
+///
+/// Cloning a `DryRunLog` is cheap and all clones share the same entries.
+///
+/// # Example
+/// ```
+/// use mastodon_api::MastodonClient;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let client = MastodonClient::new("https://mastodon.social")
+///     .with_token("your_token")
+///     .with_dry_run(true);
+///
+/// client.notifications().clear().await.unwrap();
+///
+/// let planned = client.dry_run_log().entries();
+/// assert_eq!(planned[0].url.path(), "/api/v1/notifications/clear");
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DryRunLog {
+    entries: Arc<Mutex<Vec<PlannedRequest>>>,
+}
+
+impl DryRunLog {
+    /// Creates a new, empty `DryRunLog`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the intercepted requests, in the order they were made.
+    pub fn entries(&self) -> Vec<PlannedRequest> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    /// Removes all entries.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Returns the number of intercepted requests.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Returns `true` if no requests were intercepted.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Records the request and returns the error reporting that it was not sent.
+    pub(crate) fn record(&self, request: &reqwest::Request) -> MastodonError {
+        let planned = PlannedRequest {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(<[u8]>::to_vec)
+                .unwrap_or_default(),
+        };
+        let error = MastodonError::DryRun {
+            method: planned.method.clone(),
+            url: planned.url.to_string(),
+        };
+        self.entries.lock().unwrap().push(planned);
+        error
+    }
+}
+
+/// Whether a request with the given method may change state on the server.
+pub(crate) fn is_mutation(method: &Method) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
@@ -1,3 +1,10 @@
+use crate::rate_limit::{RateLimit, parse_timestamp};
+use crate::response::HttpResponse;
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::SystemTime;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -11,15 +18,84 @@ pub enum MastodonError {
     #[error("JSON serialization/deserialization failed: {0}")]
     Serde(#[from] serde_json::Error),
 
+    /// The server answered with an error status that has no dedicated variant.
+    ///
+    /// `response` is `None` if the error was raised locally from an unexpected body.
     #[error("API error (status {status}): {message}")]
     ApiError {
         status: reqwest::StatusCode,
         message: String,
+        response: Option<Box<ApiErrorResponse>>,
     },
 
-    #[error("Authentication failed: {0}")]
-    AuthError(String),
+    /// The requested resource does not exist or is not visible to the user (404).
+    #[error("Not found: {}", .response.message())]
+    NotFound { response: Box<ApiErrorResponse> },
 
+    /// The access token is missing, invalid or revoked (401).
+    #[error("Unauthorized: {}", .response.message())]
+    Unauthorized { response: Box<ApiErrorResponse> },
+
+    /// The token lacks the required scope or the action is not permitted (403).
+    #[error("Forbidden: {}", .response.message())]
+    Forbidden { response: Box<ApiErrorResponse> },
+
+    /// The request was well-formed but failed validation (422).
+    ///
+    /// `details` maps field names to their validation errors, when the server provides them.
+    #[error("Unprocessable entity: {}", .response.message())]
+    Unprocessable {
+        details: HashMap<String, Vec<FieldError>>,
+        response: Box<ApiErrorResponse>,
+    },
+
+    /// The rate limit was exceeded (429). `reset_at` is when the budget is restored.
+    #[error("Rate limited: {}", .response.message())]
+    RateLimited {
+        reset_at: Option<SystemTime>,
+        response: Box<ApiErrorResponse>,
+    },
+
+    /// The resource has been deleted permanently (410).
+    #[error("Gone: {}", .response.message())]
+    Gone { response: Box<ApiErrorResponse> },
+
+    /// The server is temporarily unavailable, e.g. during maintenance (503).
+    #[error("Service unavailable: {}", .response.message())]
+    ServiceUnavailable { response: Box<ApiErrorResponse> },
+
+    /// The instance does not support the feature the endpoint belongs to.
+    #[error("The instance (version {version}) does not support {feature}")]
+    Unsupported {
+        feature: crate::capabilities::Feature,
+        version: String,
+    },
+
+    /// The access token was not granted a scope the endpoint requires.
+    #[error("The access token lacks the `{scope}` scope required by {endpoint}")]
+    MissingScope {
+        scope: crate::scopes::Scope,
+        endpoint: String,
+    },
+
+    /// The request was recorded instead of sent, as the client is in dry-run mode.
+    #[error("Dry run: {method} {url} was not sent")]
+    DryRun {
+        method: reqwest::Method,
+        url: String,
+    },
+
+    /// An endpoint requiring authentication was called without an access token, or
+    /// obtaining a token failed.
+    ///
+    /// `response` holds the 401 response, if the server rejected the request.
+    #[error("Authentication failed: {message}")]
+    AuthError {
+        message: String,
+        response: Option<Box<ApiErrorResponse>>,
+    },
+
+    /// The rate limit budget is spent and the reset lies beyond the configured maximum wait.
     #[error("Rate limit exceeded. Retry after: {0}")]
     RateLimit(String),
 
@@ -27,4 +103,145 @@ pub enum MastodonError {
     Custom(String),
 }
 
+/// The error response returned by the API, as `{"error": ..., "error_description": ...}`.
+#[derive(Debug, Clone)]
+pub struct ApiErrorResponse {
+    /// Status code of the response.
+    pub status: StatusCode,
+    /// The `error` field of the body, or the raw body if it is not JSON.
+    pub error: String,
+    /// The `error_description` field of the body, sent with OAuth errors.
+    pub error_description: Option<String>,
+    /// Headers of the response.
+    pub headers: HeaderMap,
+}
+
+impl ApiErrorResponse {
+    /// Returns the error, followed by its description if present.
+    pub fn message(&self) -> String {
+        match &self.error_description {
+            Some(description) => format!("{} ({})", self.error, description),
+            None => self.error.clone(),
+        }
+    }
+
+    /// Returns the rate limit state reported with the error.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        RateLimit::from_headers(&self.headers)
+    }
+
+    /// Returns the `X-Request-Id` assigned by the server, useful for support requests.
+    pub fn request_id(&self) -> Option<&str> {
+        self.headers.get("X-Request-Id")?.to_str().ok()
+    }
+}
+
+/// A validation error for a single field of a request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldError {
+    /// Machine-readable error code, e.g. `ERR_TAKEN`.
+    pub error: String,
+    /// Human-readable description, e.g. "is already taken".
+    pub description: String,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: String,
+    error_description: Option<String>,
+    #[serde(default)]
+    details: HashMap<String, Vec<FieldError>>,
+}
+
+impl MastodonError {
+    /// Builds the error for an unsuccessful response.
+    ///
+    /// `authenticated` tells whether the request carried an access token; a 401 for a
+    /// request without one is reported as [`MastodonError::AuthError`].
+    pub(crate) fn from_response(response: &HttpResponse, authenticated: bool) -> Self {
+        let status = response.status;
+        let (error, error_description, details) =
+            match serde_json::from_slice::<ErrorBody>(&response.body) {
+                Ok(body) => (body.error, body.error_description, body.details),
+                Err(_) => (response.text(), None, HashMap::new()),
+            };
+        let api_response = Box::new(ApiErrorResponse {
+            status,
+            error,
+            error_description,
+            headers: response.headers.clone(),
+        });
+
+        match status {
+            StatusCode::UNAUTHORIZED if !authenticated => MastodonError::AuthError {
+                message: format!("no access token configured: {}", api_response.message()),
+                response: Some(api_response),
+            },
+            StatusCode::UNAUTHORIZED => MastodonError::Unauthorized {
+                response: api_response,
+            },
+            StatusCode::FORBIDDEN => MastodonError::Forbidden {
+                response: api_response,
+            },
+            StatusCode::NOT_FOUND => MastodonError::NotFound {
+                response: api_response,
+            },
+            StatusCode::GONE => MastodonError::Gone {
+                response: api_response,
+            },
+            StatusCode::UNPROCESSABLE_ENTITY => MastodonError::Unprocessable {
+                details,
+                response: api_response,
+            },
+            StatusCode::TOO_MANY_REQUESTS => MastodonError::RateLimited {
+                reset_at: api_response
+                    .headers
+                    .get("X-RateLimit-Reset")
+                    .and_then(|reset| reset.to_str().ok())
+                    .and_then(parse_timestamp),
+                response: api_response,
+            },
+            StatusCode::SERVICE_UNAVAILABLE => MastodonError::ServiceUnavailable {
+                response: api_response,
+            },
+            _ => MastodonError::ApiError {
+                status,
+                message: api_response.message(),
+                response: Some(api_response),
+            },
+        }
+    }
+
+    /// Returns the HTTP status of the response that caused this error, if any.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            MastodonError::ApiError { status, .. } => Some(*status),
+            MastodonError::Reqwest(err) => err.status(),
+            MastodonError::AuthError { .. } => Some(StatusCode::UNAUTHORIZED),
+            _ => self.response().map(|response| response.status),
+        }
+    }
+
+    /// Returns the parsed error response, for errors caused by an error response.
+    pub fn response(&self) -> Option<&ApiErrorResponse> {
+        match self {
+            MastodonError::ApiError { response, .. }
+            | MastodonError::AuthError { response, .. } => response.as_deref(),
+            MastodonError::NotFound { response }
+            | MastodonError::Unauthorized { response }
+            | MastodonError::Forbidden { response }
+            | MastodonError::Unprocessable { response, .. }
+            | MastodonError::RateLimited { response, .. }
+            | MastodonError::Gone { response }
+            | MastodonError::ServiceUnavailable { response } => Some(response),
+            _ => None,
+        }
+    }
+
+    /// Returns the rate limit state reported with the error response, if any.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.response()?.rate_limit()
+    }
+}
+
 pub type Result<T> = std::result::Result<T, MastodonError>;
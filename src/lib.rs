@@ -36,29 +36,76 @@
 //! }
 //! ```
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod builder;
+pub mod cache;
+pub mod capabilities;
+pub mod cassette;
+pub mod concurrency;
+pub mod credentials;
+pub mod dry_run;
 pub mod error;
 pub mod methods;
+pub mod middleware;
 pub mod models;
+pub mod oauth;
+pub mod outbox;
 pub mod paging;
+pub mod pool;
+pub mod rate_limit;
+pub mod response;
+pub mod retry;
+pub mod scopes;
 pub mod streaming;
+mod trace;
+pub mod transport;
 
-pub use error::{MastodonError, Result};
+pub use builder::MastodonClientBuilder;
+pub use cache::ResponseCache;
+pub use concurrency::ConcurrencyLimiter;
+pub use error::{ApiErrorResponse, FieldError, MastodonError, Result};
 pub use models::{
     Account, Announcement, AnnouncementReaction, FeaturedTag, Marker, Preferences, Relationship,
     Report, Status, Suggestion, Tag, WebPushAlerts, WebPushSubscription,
 };
-
-use reqwest::{Client, RequestBuilder};
+pub use rate_limit::{RateLimit, RateLimiter};
+pub use response::Response;
+pub use retry::RetryPolicy;
+
+use capabilities::{Capabilities, Feature, ServerVersion};
+use credentials::CredentialStore;
+use dry_run::DryRunLog;
+use middleware::Middleware;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use response::HttpResponse;
+use scopes::Scopes;
 use serde::de::DeserializeOwned;
+use std::sync::{Arc, RwLock};
+use transport::{ReqwestTransport, Transport};
 
 /// The main entry point for interacting with the Mastodon API.
 ///
 /// Use `MastodonClient::new` to create a new instance, and `with_token` to authenticate.
+///
+/// Clones share the same connection pool and [`RateLimiter`], so a single client can be
+/// handed to several tasks without them exceeding the rate limit of the token.
 #[derive(Clone)]
 pub struct MastodonClient {
     base_url: String,
     client: Client,
     access_token: Option<String>,
+    rate_limiter: RateLimiter,
+    concurrency: ConcurrencyLimiter,
+    retry_policy: RetryPolicy,
+    connection: builder::ConnectionConfig,
+    middleware: Vec<Arc<dyn Middleware>>,
+    transport: Arc<dyn Transport>,
+    cache: Option<ResponseCache>,
+    capabilities: Arc<RwLock<Capabilities>>,
+    dry_run: bool,
+    dry_run_log: DryRunLog,
+    scopes: Option<Scopes>,
 }
 
 impl MastodonClient {
@@ -70,16 +117,46 @@ impl MastodonClient {
     /// let client = MastodonClient::new("https://mastodon.social");
     /// ```
     pub fn new(instance_url: &str) -> Self {
+        let client = Client::new();
         Self {
             base_url: instance_url.trim_end_matches('/').to_string(),
-            client: Client::new(),
+            transport: Arc::new(ReqwestTransport::new(client.clone())),
+            client,
             access_token: None,
+            rate_limiter: RateLimiter::new(),
+            concurrency: ConcurrencyLimiter::new(),
+            retry_policy: RetryPolicy::default(),
+            connection: builder::ConnectionConfig::default(),
+            middleware: Vec::new(),
+            cache: None,
+            capabilities: Arc::new(RwLock::new(Capabilities::default())),
+            dry_run: false,
+            dry_run_log: DryRunLog::new(),
+            scopes: None,
         }
     }
 
+    /// Returns a builder for configuring timeouts, the user agent, proxies and more.
+    ///
+    /// # Example
+    /// ```
+    /// use mastodon_api::MastodonClient;
+    /// let client = MastodonClient::builder("https://mastodon.social")
+    ///     .user_agent("my-bot/1.0")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder(instance_url: &str) -> MastodonClientBuilder {
+        MastodonClientBuilder::new(instance_url)
+    }
+
     /// Sets the access token for the client, enabling authenticated requests.
+    ///
+    /// Scopes recorded for a previous token are cleared; use
+    /// [`with_scopes`](Self::with_scopes) afterwards to record the new token's scopes.
     pub fn with_token(mut self, token: &str) -> Self {
         self.access_token = Some(token.to_string());
+        self.scopes = None;
         self
     }
 
@@ -88,61 +165,444 @@ impl MastodonClient {
         self.access_token.as_deref()
     }
 
+    /// Records the scopes granted to the access token.
+    ///
+    /// Afterwards, requests to endpoints needing a scope that was not granted fail with
+    /// [`MastodonError::MissingScope`] without being sent. Clients returned by
+    /// [`OAuth`](oauth::OAuth) and [`with_stored_token`](Self::with_stored_token) have
+    /// their scopes set already, unless the token lists none or one this crate does not
+    /// know.
+    pub fn with_scopes(mut self, scopes: Scopes) -> Self {
+        self.scopes = Some(scopes);
+        self
+    }
+
+    /// Returns the scopes granted to the access token, if known.
+    pub fn scopes(&self) -> Option<&Scopes> {
+        self.scopes.as_ref()
+    }
+
+    /// Creates a client for the instance, authenticated with the account's stored token.
+    ///
+    /// Fails with [`MastodonError::AuthError`] if the store has no token for the account.
+    pub async fn from_store(
+        instance_url: &str,
+        store: &dyn CredentialStore,
+        account: &str,
+    ) -> Result<Self> {
+        Self::new(instance_url)
+            .with_stored_token(store, account)
+            .await
+    }
+
+    /// Sets the access token to the account's stored token.
+    ///
+    /// Use this with clients created by [`builder`](Self::builder). Fails with
+    /// [`MastodonError::AuthError`] if the store has no token for the account.
+    pub async fn with_stored_token(
+        self,
+        store: &dyn CredentialStore,
+        account: &str,
+    ) -> Result<Self> {
+        match store.load_token(&self.base_url, account).await? {
+            Some(token) => {
+                let mut client = self.with_token(&token.access_token);
+                client.scopes = token.scopes();
+                Ok(client)
+            }
+            None => Err(MastodonError::AuthError {
+                message: format!("no stored token for {} on {}", account, self.base_url),
+                response: None,
+            }),
+        }
+    }
+
+    /// Returns the app stored for the instance, registering and storing it first if
+    /// the store has none.
+    ///
+    /// Corresponds to `POST /api/v1/apps` when the app is registered.
+    pub async fn load_or_register_app(
+        &self,
+        store: &dyn CredentialStore,
+        params: &methods::apps::RegisterAppParams,
+    ) -> Result<methods::apps::AppRegistration> {
+        if let Some(app) = store.load_app(&self.base_url).await? {
+            return Ok(app);
+        }
+        let app = self.apps().register(params).await?;
+        store.save_app(&self.base_url, &app).await?;
+        Ok(app)
+    }
+
     /// Returns the base URL of the Mastodon instance.
     pub fn base_url(&self) -> &str {
         &self.base_url
     }
 
     /// Returns a reference to the underlying HTTP client.
+    ///
+    /// Handlers use it to build requests; sending them is left to the [`Transport`].
     pub fn http_client(&self) -> &Client {
         &self.client
     }
 
+    /// Replaces the transport that sends requests over the network.
+    ///
+    /// Use a [`MockTransport`](transport::MockTransport) to test code built on the
+    /// client without network access.
+    pub fn with_transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// Replaces the rate limiter of the client.
+    ///
+    /// Use this to share one budget between several clients authenticated with the
+    /// same token, or to configure a reserve or maximum wait.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Returns the rate limiter governing the requests of this client.
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+
+    /// Returns the last rate limit state reported by the server, if any.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limiter.current()
+    }
+
+    /// Limits the number of requests this client has in flight at the same time.
+    ///
+    /// Requests over the limit wait for a free slot. Applies to every handler and
+    /// to paged requests.
+    pub fn with_concurrency_limiter(mut self, limiter: ConcurrencyLimiter) -> Self {
+        self.concurrency = limiter;
+        self
+    }
+
+    /// Returns the concurrency limiter of the client.
+    pub fn concurrency_limiter(&self) -> &ConcurrencyLimiter {
+        &self.concurrency
+    }
+
+    /// Sets the policy deciding which failed requests are retried.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Returns the retry policy of the client.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Adds a middleware that runs for every request made by this client.
+    ///
+    /// Middleware runs in the order it was added and also applies to paged requests
+    /// and streaming connections.
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Enables caching of `GET` responses with the given cache.
+    ///
+    /// See [`ResponseCache`] for how responses are revalidated and expired.
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Returns the response cache of the client, if caching is enabled.
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+
+    /// Enables or disables dry-run mode.
+    ///
+    /// In dry-run mode, requests that may change anything on the server are recorded
+    /// in the [`dry_run_log`](Self::dry_run_log) instead of being sent. Endpoints
+    /// returning `()` succeed; all others fail with [`MastodonError::DryRun`].
+    pub fn with_dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = enabled;
+        self
+    }
+
+    /// Returns whether the client is in dry-run mode.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Returns the requests intercepted in dry-run mode.
+    pub fn dry_run_log(&self) -> &DryRunLog {
+        &self.dry_run_log
+    }
+
+    /// Fetches the instance metadata and records which features the instance supports.
+    ///
+    /// Afterwards, handlers for unsupported endpoints fail with
+    /// [`MastodonError::Unsupported`] without sending a request. Manual overrides made
+    /// with [`set_feature`](Self::set_feature) are kept.
+    ///
+    /// Corresponds to `GET /api/v1/instance`.
+    pub async fn detect_capabilities(&self) -> Result<Capabilities> {
+        let instance = self.instance().get().await?;
+        let mut capabilities = self.capabilities.write().unwrap();
+        capabilities.set_version(ServerVersion::parse(&instance.version));
+        Ok(capabilities.clone())
+    }
+
+    /// Returns the features known to be supported by the instance.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities.read().unwrap().clone()
+    }
+
+    /// Replaces the known capabilities, e.g. with ones detected by another client.
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = Arc::new(RwLock::new(capabilities));
+        self
+    }
+
+    /// Marks a feature as supported or unsupported, overriding detection.
+    ///
+    /// Useful for forks that backport features to an older version number.
+    pub fn set_feature(&self, feature: Feature, enabled: bool) {
+        self.capabilities.write().unwrap().set(feature, enabled);
+    }
+
+    /// Fails with [`MastodonError::Unsupported`] if the instance lacks the feature.
+    pub(crate) fn require(&self, feature: Feature) -> Result<()> {
+        self.capabilities.read().unwrap().require(feature)
+    }
+
     pub(crate) async fn send<T: DeserializeOwned>(&self, builder: RequestBuilder) -> Result<T> {
-        let mut retries = 0;
-        let max_retries = 3;
+        let response = self.execute(builder).await?;
+        response.json()
+    }
+
+    /// Sends a request and returns the deserialized body along with the response metadata.
+    pub(crate) async fn send_with_meta<T: DeserializeOwned>(
+        &self,
+        builder: RequestBuilder,
+    ) -> Result<Response<T>> {
+        Response::from_http(self.execute(builder).await?)
+    }
+
+    /// Sends a request to an endpoint that returns nothing of interest.
+    ///
+    /// Succeeds for `204 No Content`, an empty body or any JSON body such as `{}`,
+    /// which Mastodon returns inconsistently across endpoints and versions.
+    pub(crate) async fn send_no_content(&self, builder: RequestBuilder) -> Result<()> {
+        // A recorded dry run stands in for the empty response, so it is not reported
+        // to the middleware as an error.
+        let response = match self.execute_unreported(builder).await {
+            Ok(response) => response,
+            Err(MastodonError::DryRun { .. }) => return Ok(()),
+            Err(err) => {
+                self.report_error(&err).await;
+                return Err(err);
+            }
+        };
+        if response.status == StatusCode::NO_CONTENT
+            || response.body.iter().all(u8::is_ascii_whitespace)
+        {
+            return Ok(());
+        }
+        response.json::<serde::de::IgnoredAny>().map(|_| ())
+    }
+
+    /// Sends a request through the middleware and rate limiter, retrying according
+    /// to the [`RetryPolicy`], and returns the successful response.
+    pub(crate) async fn execute(&self, builder: RequestBuilder) -> Result<HttpResponse> {
+        let result = self.execute_unreported(builder).await;
+        if let Err(err) = &result {
+            self.report_error(err).await;
+        }
+        result
+    }
+
+    /// Like [`execute`](Self::execute), but leaves reporting a failure to the caller.
+    async fn execute_unreported(&self, builder: RequestBuilder) -> Result<HttpResponse> {
+        let mut builder = builder;
+        if let Some(token) = &self.access_token {
+            builder = builder.bearer_auth(token);
+        }
+        match builder.build() {
+            Ok(request) => self.dispatch(request).await,
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Passes a failed request to the middleware.
+    async fn report_error(&self, err: &MastodonError) {
+        for middleware in &self.middleware {
+            middleware.on_error(err).await;
+        }
+    }
+
+    /// Checks the granted scopes, records mutations in dry-run mode, and sends
+    /// everything else.
+    async fn dispatch(&self, request: reqwest::Request) -> Result<HttpResponse> {
+        self.check_scopes(&request)?;
+        if self.dry_run && dry_run::is_mutation(request.method()) {
+            return Err(self.dry_run_log.record(&request));
+        }
+        let span = trace::request_span(request.method(), request.url().path());
+        let result = trace::instrument(&span, self.execute_cached(request, &span)).await;
+        if let Err(err) = &result {
+            trace::failure(&span, err);
+        }
+        result
+    }
+
+    /// Fails with [`MastodonError::MissingScope`] if the granted scopes are known and
+    /// include none of the scopes the endpoint requires.
+    fn check_scopes(&self, request: &reqwest::Request) -> Result<()> {
+        let Some(granted) = self.scopes.as_ref().filter(|_| self.access_token.is_some()) else {
+            return Ok(());
+        };
+        let required = scopes::required(request.method(), request.url().path());
+        match required.first() {
+            Some(&scope) if !required.iter().any(|scope| granted.allows(*scope)) => {
+                Err(MastodonError::MissingScope {
+                    scope,
+                    endpoint: format!("{} {}", request.method(), request.url().path()),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Serves `GET` requests from the [`ResponseCache`] where possible.
+    async fn execute_cached(
+        &self,
+        mut request: reqwest::Request,
+        span: &trace::Span,
+    ) -> Result<HttpResponse> {
+        let Some(cache) = self
+            .cache
+            .as_ref()
+            .filter(|_| request.method() == reqwest::Method::GET)
+        else {
+            return self.execute_with_retries(request, span).await;
+        };
+        let key = ResponseCache::key(&request);
+        let unconditional = request.try_clone();
+        if let Some(cached) = cache.lookup(&key, &mut request) {
+            return Ok(cached);
+        }
+        let response = self.execute_with_retries(request, span).await?;
+        if let Some(response) = cache.store(&key, response) {
+            return Ok(response);
+        }
+
+        // The cached response went away before the 304 arrived; ask again without
+        // validators to get the full body.
+        let not_modified =
+            || MastodonError::Custom("304 Not Modified without a cached response".into());
+        let request = unconditional.ok_or_else(not_modified)?;
+        let response = self.execute_with_retries(request, span).await?;
+        cache.store(&key, response).ok_or_else(not_modified)
+    }
+
+    async fn execute_with_retries(
+        &self,
+        mut request: reqwest::Request,
+        span: &trace::Span,
+    ) -> Result<HttpResponse> {
+        let replayable = self
+            .retry_policy
+            .is_replayable(request.method(), request.headers());
+        let mut attempt = 1;
 
         loop {
-            let mut current_builder = builder.try_clone().ok_or(MastodonError::ApiError {
-                status: reqwest::StatusCode::INTERNAL_SERVER_ERROR,
-                message: "Failed to clone request for retry".to_string(),
-            })?;
+            // Requests with streaming bodies cannot be cloned and are only sent once.
+            let retry_request = request
+                .try_clone()
+                .filter(|_| attempt < self.retry_policy.attempts());
 
-            if let Some(token) = &self.access_token {
-                current_builder = current_builder.bearer_auth(token);
+            for middleware in &self.middleware {
+                middleware.before_request(&mut request).await?;
             }
 
-            let response = current_builder.send().await?;
-            let status = response.status();
-
-            // Handle Rate Limiting
-            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                if retries < max_retries {
-                    if let Some(reset) = response.headers().get("X-RateLimit-Reset") {
-                        if let Ok(_) = reset.to_str() {
-                            let wait_secs = 2u64.pow(retries);
-                            tokio::time::sleep(std::time::Duration::from_secs(wait_secs)).await;
-                            retries += 1;
-                            continue;
-                        }
+            self.rate_limiter.acquire().await?;
+            let permit = self
+                .concurrency
+                .acquire(request.url().host_str().unwrap_or_default())
+                .await;
+            let started = std::time::Instant::now();
+            let sent = self.transport.send(request).await;
+            drop(permit);
+            let response = match sent {
+                Ok(response) => response,
+                Err(err) => match retry_request {
+                    Some(next) if self.retry_policy.retries_error(&err, replayable) => {
+                        let delay = self.retry_policy.backoff(attempt);
+                        trace::retry(span, attempt, delay, &err);
+                        tokio::time::sleep(delay).await;
+                        request = next;
+                        attempt += 1;
+                        continue;
                     }
-                }
+                    _ => return Err(err),
+                },
+            };
+            self.rate_limiter.update(&response.headers);
+            trace::response(
+                span,
+                attempt,
+                response.status,
+                started.elapsed(),
+                RateLimit::from_headers(&response.headers).map(|limit| limit.remaining),
+            );
+            for middleware in &self.middleware {
+                middleware.after_response(&response).await?;
             }
-
-            // Handle transient server errors (5xx)
-            if status.is_server_error() && retries < max_retries {
-                let wait_secs = 2u64.pow(retries);
-                tokio::time::sleep(std::time::Duration::from_secs(wait_secs)).await;
-                retries += 1;
+            let status = response.status;
+
+            if let Some(next) = retry_request
+                && self.retry_policy.retries_status(status, replayable)
+                && !self.retry_policy.exceeds_max_delay(&response.headers)
+            {
+                let headers = &response.headers;
+                let reset_at = headers
+                    .get("X-RateLimit-Reset")
+                    .and_then(|reset| reset.to_str().ok())
+                    .and_then(rate_limit::parse_timestamp)
+                    .filter(|reset| *reset > std::time::SystemTime::now());
+
+                if let Some(wait) = self.retry_policy.retry_after(headers) {
+                    trace::retry(span, attempt, wait, &status);
+                    tokio::time::sleep(wait).await;
+                } else if let (StatusCode::TOO_MANY_REQUESTS, Some(reset_at)) = (status, reset_at) {
+                    // The limiter holds the retry back until the announced reset.
+                    let wait = reset_at
+                        .duration_since(std::time::SystemTime::now())
+                        .unwrap_or_default();
+                    trace::retry(span, attempt, wait, &status);
+                    self.rate_limiter.exhaust(reset_at);
+                } else {
+                    let delay = self.retry_policy.backoff(attempt);
+                    trace::retry(span, attempt, delay, &status);
+                    tokio::time::sleep(delay).await;
+                }
+                request = next;
+                attempt += 1;
                 continue;
             }
 
-            if !status.is_success() {
-                let message = response.text().await.unwrap_or_default();
-                return Err(MastodonError::ApiError { status, message });
+            // A 304 only arrives for requests revalidating a cached response.
+            if !status.is_success() && status != StatusCode::NOT_MODIFIED {
+                return Err(MastodonError::from_response(
+                    &response,
+                    self.access_token.is_some(),
+                ));
             }
 
-            return Ok(response.json().await?);
+            return Ok(response);
         }
     }
 
@@ -269,5 +729,7 @@ impl MastodonClient {
     /// Create a new streaming client for real-time events.
     pub fn streaming(&self) -> streaming::StreamingClient {
         streaming::StreamingClient::new(&self.base_url, self.access_token.clone())
+            .with_connection(self.connection.clone())
+            .with_middleware(self.middleware.clone())
     }
 }
@@ -1,12 +1,32 @@
 use crate::MastodonClient;
 use crate::error::Result;
 use crate::models::Account;
+use crate::oauth::Token;
+use serde::Serialize;
 
 /// Handler for account-related API endpoints.
 pub struct AccountsHandler<'a> {
     client: &'a MastodonClient,
 }
 
+/// Parameters for registering a new account.
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisterAccountParams {
+    /// The desired username for the account.
+    pub username: String,
+    /// The email address to be used for login.
+    pub email: String,
+    /// The password to be used for login.
+    pub password: String,
+    /// Whether the user agrees to the local rules, terms and policies.
+    pub agreement: bool,
+    /// The language of the confirmation email (ISO 639-1 code).
+    pub locale: String,
+    /// The reason for signing up, if the instance requires manual approval.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
 impl<'a> AccountsHandler<'a> {
     /// Creates a new `AccountsHandler` for the given client.
     ///
@@ -19,6 +39,29 @@ impl<'a> AccountsHandler<'a> {
         Self { client }
     }
 
+    /// Registers a new account on the instance.
+    ///
+    /// The client must be authenticated with an app token with the `write:accounts`
+    /// scope, as obtained by [`OAuth::authenticate_app`](crate::oauth::OAuth::authenticate_app).
+    /// The new account cannot be used before its email address is confirmed.
+    ///
+    /// Rejected fields, such as a taken username, are reported in the `details` of
+    /// [`MastodonError::Unprocessable`](crate::MastodonError::Unprocessable), keyed by
+    /// field name.
+    ///
+    /// Parameters:
+    /// - `params`: The details of the account to register.
+    ///
+    /// Returns:
+    /// - `Result<Token>`: A user token for the new account.
+    ///
+    /// Corresponds to `POST /api/v1/accounts`.
+    pub async fn register(&self, params: &RegisterAccountParams) -> Result<Token> {
+        let url = format!("{}/api/v1/accounts", self.client.base_url());
+        let req = self.client.http_client().post(&url).json(params);
+        self.client.send(req).await
+    }
+
     /// Verifies the authenticated user's credentials and returns their account info.
     ///
     /// Returns:
@@ -23,7 +23,7 @@ impl<'a> AdminAccountsHandler<'a> {
             .http_client()
             .post(&url)
             .form(&[("type", r#type)]);
-        self.client.send(req).await
+        self.client.send_no_content(req).await
     }
 
     /// Suspends an account.
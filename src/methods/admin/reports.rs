@@ -26,6 +26,6 @@ impl<'a> AdminReportsHandler<'a> {
             id
         );
         let req = self.client.http_client().post(&url);
-        self.client.send(req).await
+        self.client.send_no_content(req).await
     }
 }
@@ -41,11 +41,7 @@ impl<'a> AnnouncementsHandler<'a> {
             id
         );
         let req = self.client.http_client().post(&url);
-        // Mastodon returns 200 OK with empty body for this endpoint.
-        // MastodonClient::send expects T: DeserializeOwned, so we might need a workaround for empty bodies
-        // if send is strictly requiring a JSON body.
-        // Let's check MastodonClient::send in src/lib.rs.
-        self.client.send(req).await
+        self.client.send_no_content(req).await
     }
 
     /// Adds a reaction to an announcement.
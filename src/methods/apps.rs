@@ -1,5 +1,6 @@
 use crate::MastodonClient;
 use crate::error::Result;
+use crate::models::Application;
 use serde::{Deserialize, Serialize};
 
 /// Handler for application-related API endpoints.
@@ -14,14 +15,15 @@ pub struct RegisterAppParams {
     pub client_name: String,
     /// Where to redirect the user after authorization. Use `urn:ietf:wg:oauth:2.0:oob` for local apps.
     pub redirect_uris: String,
-    /// Space-separated list of scopes (e.g., "read write follow").
+    /// Space-separated list of scopes (e.g., "read write follow"), as formatted by
+    /// [`Scopes`](crate::scopes::Scopes).
     pub scopes: String,
     /// The website of your application.
     pub website: Option<String>,
 }
 
 /// Response from a successful application registration.
-#[derive(Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppRegistration {
     pub id: String,
     pub name: String,
@@ -51,4 +53,18 @@ impl<'a> AppsHandler<'a> {
         let req = self.client.http_client().post(&url).json(params);
         self.client.send(req).await
     }
+
+    /// Verifies the app credentials of the client's access token.
+    ///
+    /// Works with both app tokens (from the `client_credentials` grant) and user tokens.
+    ///
+    /// Returns:
+    /// - `Result<Application>`: The application the token belongs to.
+    ///
+    /// Corresponds to `GET /api/v1/apps/verify_credentials`.
+    pub async fn verify_credentials(&self) -> Result<Application> {
+        let url = format!("{}/api/v1/apps/verify_credentials", self.client.base_url());
+        let req = self.client.http_client().get(&url);
+        self.client.send(req).await
+    }
 }
@@ -1,5 +1,7 @@
 use crate::MastodonClient;
 use crate::error::Result;
+use crate::response::Response;
+use reqwest::RequestBuilder;
 use serde::de::DeserializeOwned;
 
 /// A builder for requests that return a list of items (e.g., timelines, account lists).
@@ -53,6 +55,18 @@ impl<'a, T: DeserializeOwned> ListBuilder<'a, T> {
 
     /// Executes the request and returns the list of results.
     pub async fn send(self) -> Result<Vec<T>> {
+        let client = self.client;
+        client.send(self.build()).await
+    }
+
+    /// Executes the request and returns the list of results along with the
+    /// response metadata, such as the pagination links.
+    pub async fn send_with_meta(self) -> Result<Response<Vec<T>>> {
+        let client = self.client;
+        client.send_with_meta(self.build()).await
+    }
+
+    fn build(self) -> RequestBuilder {
         let mut req = self.client.http_client().get(&self.url);
 
         if let Some(l) = self.limit {
@@ -68,6 +82,6 @@ impl<'a, T: DeserializeOwned> ListBuilder<'a, T> {
             req = req.query(&[("max_id", max)]);
         }
 
-        self.client.send(req).await
+        req
     }
 }
@@ -1,4 +1,5 @@
 use crate::MastodonClient;
+use crate::capabilities::Feature;
 use crate::error::Result;
 use crate::models::Search;
 
@@ -75,6 +76,7 @@ impl<'a> SearchBuilder<'a> {
 
     /// Executes the search request (v2).
     pub async fn send(self) -> Result<Search> {
+        self.client.require(Feature::SearchV2)?;
         let url = format!("{}/api/v2/search", self.client.base_url());
         let mut req = self
             .client
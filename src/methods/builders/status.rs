@@ -15,15 +15,17 @@ impl<'a> StatusBuilder<'a> {
             client,
             params: CreateStatusParams {
                 status: text.to_string(),
-                in_reply_to_id: None,
-                sensitive: false,
-                spoiler_text: None,
-                visibility: None,
-                language: None,
+                ..Default::default()
             },
         }
     }
 
+    /// Attach an uploaded media attachment by its ID.
+    pub fn media_id(mut self, value: &str) -> Self {
+        self.params.media_ids.push(value.to_string());
+        self
+    }
+
     /// ID of the status being replied to.
     pub fn in_reply_to_id(mut self, value: &str) -> Self {
         self.params.in_reply_to_id = Some(value.to_string());
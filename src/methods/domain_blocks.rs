@@ -40,7 +40,7 @@ impl<'a> DomainBlocksHandler<'a> {
             .http_client()
             .post(&url)
             .form(&[("domain", domain)]);
-        self.client.send(req).await
+        self.client.send_no_content(req).await
     }
 
     /// Unblocks a domain.
@@ -59,6 +59,6 @@ impl<'a> DomainBlocksHandler<'a> {
             .http_client()
             .delete(&url)
             .form(&[("domain", domain)]);
-        self.client.send(req).await
+        self.client.send_no_content(req).await
     }
 }
@@ -74,7 +74,6 @@ impl<'a> FiltersHandler<'a> {
     pub async fn delete(&self, id: &str) -> Result<()> {
         let url = format!("{}/api/v1/filters/{}", self.client.base_url(), id);
         let req = self.client.http_client().delete(&url);
-        let _: serde_json::Value = self.client.send(req).await?;
-        Ok(())
+        self.client.send_no_content(req).await
     }
 }
@@ -77,8 +77,7 @@ impl<'a> ListsHandler<'a> {
     pub async fn delete(&self, id: &str) -> Result<()> {
         let url = format!("{}/api/v1/lists/{}", self.client.base_url(), id);
         let req = self.client.http_client().delete(&url);
-        let _: serde_json::Value = self.client.send(req).await?;
-        Ok(())
+        self.client.send_no_content(req).await
     }
 
     /// Adds accounts to a list.
@@ -102,8 +101,7 @@ impl<'a> ListsHandler<'a> {
             .http_client()
             .post(&url)
             .json(&serde_json::json!({ "account_ids": account_ids }));
-        let _: serde_json::Value = self.client.send(req).await?;
-        Ok(())
+        self.client.send_no_content(req).await
     }
 
     /// Removes accounts from a list.
@@ -127,7 +125,6 @@ impl<'a> ListsHandler<'a> {
             .http_client()
             .delete(&url)
             .json(&serde_json::json!({ "account_ids": account_ids }));
-        let _: serde_json::Value = self.client.send(req).await?;
-        Ok(())
+        self.client.send_no_content(req).await
     }
 }
@@ -57,6 +57,7 @@ impl<'a> MarkersHandler<'a> {
             .ok_or_else(|| crate::error::MastodonError::ApiError {
                 status: reqwest::StatusCode::OK,
                 message: format!("Marker for {} not returned", timeline),
+                response: None,
             })
     }
 }
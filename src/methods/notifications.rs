@@ -1,6 +1,7 @@
 use crate::MastodonClient;
 use crate::error::Result;
 use crate::models::Notification;
+use crate::response::Response;
 
 /// Handler for notification-related API endpoints.
 pub struct NotificationsHandler<'a> {
@@ -31,6 +32,19 @@ impl<'a> NotificationsHandler<'a> {
         self.client.send(req).await
     }
 
+    /// Fetches all notifications along with the response metadata, such as the
+    /// pagination links and rate limit state.
+    ///
+    /// Returns:
+    /// - `Result<Response<Vec<Notification>>>`: The fetched notifications and metadata.
+    ///
+    /// Corresponds to `GET /api/v1/notifications`.
+    pub async fn list_with_meta(&self) -> Result<Response<Vec<Notification>>> {
+        let url = format!("{}/api/v1/notifications", self.client.base_url());
+        let req = self.client.http_client().get(&url);
+        self.client.send_with_meta(req).await
+    }
+
     /// Fetches a specific notification by its ID.
     ///
     /// Parameters:
@@ -55,8 +69,7 @@ impl<'a> NotificationsHandler<'a> {
     pub async fn clear(&self) -> Result<()> {
         let url = format!("{}/api/v1/notifications/clear", self.client.base_url());
         let req = self.client.http_client().post(&url);
-        let _: serde_json::Value = self.client.send(req).await?;
-        Ok(())
+        self.client.send_no_content(req).await
     }
 
     /// Dismisses a single notification by its ID.
@@ -75,7 +88,6 @@ impl<'a> NotificationsHandler<'a> {
             id
         );
         let req = self.client.http_client().post(&url);
-        let _: serde_json::Value = self.client.send(req).await?;
-        Ok(())
+        self.client.send_no_content(req).await
     }
 }
@@ -120,6 +120,6 @@ impl<'a> PushHandler<'a> {
     pub async fn unsubscribe(&self) -> Result<()> {
         let url = format!("{}/api/v1/push/subscription", self.client.base_url());
         let req = self.client.http_client().delete(&url);
-        self.client.send(req).await
+        self.client.send_no_content(req).await
     }
 }
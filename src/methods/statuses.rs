@@ -2,7 +2,8 @@ use crate::MastodonClient;
 use crate::error::Result;
 use crate::methods::builders::StatusBuilder;
 use crate::models::Status;
-use serde::Serialize;
+use crate::retry::{IDEMPOTENCY_KEY_HEADER, idempotency_key};
+use serde::{Deserialize, Serialize};
 
 /// Handler for status-related API endpoints.
 pub struct StatusesHandler<'a> {
@@ -10,10 +11,13 @@ pub struct StatusesHandler<'a> {
 }
 
 /// Parameters for creating a new status.
-#[derive(Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CreateStatusParams {
     /// The text content of the status.
     pub status: String,
+    /// IDs of uploaded media attachments to attach to the status.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub media_ids: Vec<String>,
     /// ID of the status being replied to, if any.
     pub in_reply_to_id: Option<String>,
     /// Whether the status should be marked as sensitive.
@@ -53,6 +57,9 @@ impl<'a> StatusesHandler<'a> {
 
     /// Creates a new status.
     ///
+    /// The request carries a freshly generated `Idempotency-Key`, so it can be
+    /// retried safely without posting the status twice.
+    ///
     /// Parameters:
     /// - `params`: The parameters for the status to create.
     ///
@@ -61,8 +68,35 @@ impl<'a> StatusesHandler<'a> {
     ///
     /// Corresponds to `POST /api/v1/statuses`.
     pub async fn create(&self, params: &CreateStatusParams) -> Result<Status> {
+        self.create_with_idempotency_key(params, &idempotency_key())
+            .await
+    }
+
+    /// Creates a new status using the given `Idempotency-Key`.
+    ///
+    /// Mastodon ignores repeated requests with the same key for one hour, so reusing
+    /// a key across attempts (even across restarts) never posts the status twice.
+    ///
+    /// Parameters:
+    /// - `params`: The parameters for the status to create.
+    /// - `key`: A unique key identifying this status.
+    ///
+    /// Returns:
+    /// - `Result<Status>`: The created status.
+    ///
+    /// Corresponds to `POST /api/v1/statuses`.
+    pub async fn create_with_idempotency_key(
+        &self,
+        params: &CreateStatusParams,
+        key: &str,
+    ) -> Result<Status> {
         let url = format!("{}/api/v1/statuses", self.client.base_url());
-        let req = self.client.http_client().post(&url).json(params);
+        let req = self
+            .client
+            .http_client()
+            .post(&url)
+            .header(IDEMPOTENCY_KEY_HEADER, key)
+            .json(params);
         self.client.send(req).await
     }
 
@@ -78,11 +112,7 @@ impl<'a> StatusesHandler<'a> {
     pub async fn create_simple(&self, text: &str) -> Result<Status> {
         let params = CreateStatusParams {
             status: text.to_string(),
-            in_reply_to_id: None,
-            sensitive: false,
-            spoiler_text: None,
-            visibility: None,
-            language: None,
+            ..Default::default()
         };
         self.create(&params).await
     }
@@ -47,6 +47,6 @@ impl<'a> SuggestionsHandler<'a> {
             account_id
         );
         let req = self.client.http_client().delete(&url);
-        self.client.send(req).await
+        self.client.send_no_content(req).await
     }
 }
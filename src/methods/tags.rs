@@ -1,4 +1,5 @@
 use crate::MastodonClient;
+use crate::capabilities::Feature;
 use crate::error::Result;
 use crate::models::{FeaturedTag, Tag};
 
@@ -20,6 +21,7 @@ impl<'a> TagsHandler<'a> {
     ///
     /// Corresponds to `GET /api/v1/followed_tags`.
     pub async fn list_followed(&self) -> Result<Vec<Tag>> {
+        self.client.require(Feature::FollowedTags)?;
         let url = format!("{}/api/v1/followed_tags", self.client.base_url());
         let req = self.client.http_client().get(&url);
         self.client.send(req).await
@@ -35,6 +37,7 @@ impl<'a> TagsHandler<'a> {
     ///
     /// Corresponds to `POST /api/v1/tags/:name/follow`.
     pub async fn follow(&self, name: &str) -> Result<Tag> {
+        self.client.require(Feature::FollowedTags)?;
         let url = format!("{}/api/v1/tags/{}/follow", self.client.base_url(), name);
         let req = self.client.http_client().post(&url);
         self.client.send(req).await
@@ -50,6 +53,7 @@ impl<'a> TagsHandler<'a> {
     ///
     /// Corresponds to `POST /api/v1/tags/{name}/unfollow`.
     pub async fn unfollow(&self, name: &str) -> Result<Tag> {
+        self.client.require(Feature::FollowedTags)?;
         let url = format!("{}/api/v1/tags/{}/unfollow", self.client.base_url(), name);
         let req = self.client.http_client().post(&url);
         self.client.send(req).await
@@ -62,6 +66,7 @@ impl<'a> TagsHandler<'a> {
     ///
     /// Corresponds to `GET /api/v1/featured_tags`.
     pub async fn list_featured(&self) -> Result<Vec<FeaturedTag>> {
+        self.client.require(Feature::FeaturedTags)?;
         let url = format!("{}/api/v1/featured_tags", self.client.base_url());
         let req = self.client.http_client().get(&url);
         self.client.send(req).await
@@ -77,6 +82,7 @@ impl<'a> TagsHandler<'a> {
     ///
     /// Corresponds to `POST /api/v1/featured_tags`.
     pub async fn feature(&self, name: &str) -> Result<FeaturedTag> {
+        self.client.require(Feature::FeaturedTags)?;
         let url = format!("{}/api/v1/featured_tags", self.client.base_url());
         let req = self.client.http_client().post(&url).form(&[("name", name)]);
         self.client.send(req).await
@@ -92,9 +98,10 @@ impl<'a> TagsHandler<'a> {
     ///
     /// Corresponds to `DELETE /api/v1/featured_tags/:id`.
     pub async fn unfeature(&self, id: &str) -> Result<()> {
+        self.client.require(Feature::FeaturedTags)?;
         let url = format!("{}/api/v1/featured_tags/{}", self.client.base_url(), id);
         let req = self.client.http_client().delete(&url);
-        self.client.send(req).await
+        self.client.send_no_content(req).await
     }
 
     /// Fetches suggestions for tags to feature.
@@ -104,6 +111,7 @@ impl<'a> TagsHandler<'a> {
     ///
     /// Corresponds to `GET /api/v1/featured_tags/suggestions`.
     pub async fn featured_suggestions(&self) -> Result<Vec<Tag>> {
+        self.client.require(Feature::FeaturedTagSuggestions)?;
         let url = format!(
             "{}/api/v1/featured_tags/suggestions",
             self.client.base_url()
@@ -1,6 +1,7 @@
 use crate::MastodonClient;
 use crate::error::Result;
 use crate::models::Status;
+use crate::response::Response;
 
 /// Handler for timeline-related API endpoints.
 pub struct TimelinesHandler<'a> {
@@ -29,6 +30,16 @@ impl<'a> TimelinesHandler<'a> {
         Ok(crate::paging::PagedRequest::new(self.client, url))
     }
 
+    /// Fetches the public timeline along with the response metadata, such as the
+    /// pagination links and rate limit state.
+    ///
+    /// Corresponds to `GET /api/v1/timelines/public`.
+    pub async fn public_with_meta(&self) -> Result<Response<Vec<Status>>> {
+        let url = format!("{}/api/v1/timelines/public", self.client.base_url());
+        let req = self.client.http_client().get(&url);
+        self.client.send_with_meta(req).await
+    }
+
     /// Fetches the home timeline for the authenticated user.
     ///
     /// Corresponds to `GET /api/v1/timelines/home`.
@@ -37,4 +48,14 @@ impl<'a> TimelinesHandler<'a> {
         let req = self.client.http_client().get(&url);
         self.client.send(req).await
     }
+
+    /// Fetches the home timeline along with the response metadata, such as the
+    /// pagination links and rate limit state.
+    ///
+    /// Corresponds to `GET /api/v1/timelines/home`.
+    pub async fn home_with_meta(&self) -> Result<Response<Vec<Status>>> {
+        let url = format!("{}/api/v1/timelines/home", self.client.base_url());
+        let req = self.client.http_client().get(&url);
+        self.client.send_with_meta(req).await
+    }
 }
@@ -1,4 +1,5 @@
 use crate::MastodonClient;
+use crate::capabilities::Feature;
 use crate::error::Result;
 use crate::models::{Status, Tag};
 
@@ -25,6 +26,7 @@ impl<'a> TrendsHandler<'a> {
     ///
     /// Corresponds to `GET /api/v1/trends/statuses`.
     pub async fn statuses(&self) -> Result<Vec<Status>> {
+        self.client.require(Feature::TrendingStatuses)?;
         let url = format!("{}/api/v1/trends/statuses", self.client.base_url());
         let req = self.client.http_client().get(&url);
         self.client.send(req).await
@@ -0,0 +1,77 @@
+//! Hooks for cross-cutting concerns such as logging, metrics or header injection.
+//!
+//! Middleware registered on a [`MastodonClient`](crate::MastodonClient) runs for every
+//! API call, every page fetched by a [`PagedRequest`](crate::paging::PagedRequest) and
+//! every streaming connection.
+
+use crate::error::{MastodonError, Result};
+use crate::response::HttpResponse;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// A hook into the request lifecycle of a [`MastodonClient`](crate::MastodonClient).
+///
+/// All methods have empty default implementations, so a middleware only implements
+/// the stages it cares about. Several middleware can be stacked; they run in the
+/// order they were added.
+///
+/// # Example
+/// ```
+/// use async_trait::async_trait;
+/// use mastodon_api::middleware::Middleware;
+/// use mastodon_api::response::HttpResponse;
+/// use mastodon_api::{MastodonClient, Result};
+///
+/// struct Logger;
+///
+/// #[async_trait]
+/// impl Middleware for Logger {
+///     async fn after_response(&self, response: &HttpResponse) -> Result<()> {
+///         println!("{} {} -> {}", response.method, response.url.path(), response.status);
+///         Ok(())
+///     }
+/// }
+///
+/// let client = MastodonClient::new("https://mastodon.social").with_middleware(Logger);
+/// ```
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    /// Called before each attempt to send a request, including retries.
+    ///
+    /// The request can be modified, e.g. to add headers. Returning an error aborts
+    /// the request with that error. Note that the `Authorization` header has already
+    /// been set at this point.
+    async fn before_request(&self, request: &mut reqwest::Request) -> Result<()> {
+        let _ = request;
+        Ok(())
+    }
+
+    /// Called for each response received, before it is checked for errors.
+    ///
+    /// Returning an error fails the request with that error.
+    async fn after_response(&self, response: &HttpResponse) -> Result<()> {
+        let _ = response;
+        Ok(())
+    }
+
+    /// Called once when a request finally fails, after all retries.
+    async fn on_error(&self, error: &MastodonError) {
+        let _ = error;
+    }
+}
+
+/// Lets a middleware be shared, e.g. to read counters it collects after registering it.
+#[async_trait]
+impl<M: Middleware + ?Sized> Middleware for Arc<M> {
+    async fn before_request(&self, request: &mut reqwest::Request) -> Result<()> {
+        (**self).before_request(request).await
+    }
+
+    async fn after_response(&self, response: &HttpResponse) -> Result<()> {
+        (**self).after_response(response).await
+    }
+
+    async fn on_error(&self, error: &MastodonError) {
+        (**self).on_error(error).await
+    }
+}
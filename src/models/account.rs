@@ -1,3 +1,4 @@
+use crate::models::CustomEmoji;
 use serde::{Deserialize, Serialize};
 
 /// Represents a user account on Mastodon.
@@ -32,4 +33,96 @@ pub struct Account {
     pub bot: bool,
     /// The time the account was created (ISO 8601).
     pub created_at: String,
+    /// URL of a static version of the avatar, for animated avatars.
+    #[serde(default)]
+    pub avatar_static: String,
+    /// URL of a static version of the header, for animated headers.
+    #[serde(default)]
+    pub header_static: String,
+    /// Additional metadata shown on the profile as name-value pairs.
+    #[serde(default)]
+    pub fields: Vec<Field>,
+    /// Custom emoji to be used when rendering the display name, note and fields.
+    #[serde(default)]
+    pub emojis: Vec<CustomEmoji>,
+    /// The new account of a user who has moved, if any.
+    #[serde(default)]
+    pub moved: Option<Box<Account>>,
+    /// Whether the account has opted into discovery features such as the profile directory.
+    #[serde(default)]
+    pub discoverable: Option<bool>,
+    /// Whether the account represents a group actor.
+    #[serde(default)]
+    pub group: bool,
+    /// Whether the user has asked search engines not to index their profile.
+    #[serde(default)]
+    pub noindex: Option<bool>,
+    /// Whether the account has been suspended by a moderator.
+    #[serde(default)]
+    pub suspended: Option<bool>,
+    /// Whether the account has been limited (hidden) by a moderator.
+    #[serde(default)]
+    pub limited: Option<bool>,
+    /// The date of the account's latest status (ISO 8601 date), if any.
+    #[serde(default)]
+    pub last_status_at: Option<String>,
+    /// The roles of the account that are set to be highlighted on the profile.
+    #[serde(default)]
+    pub roles: Vec<AccountRole>,
+    /// Profile data for editing, only returned for the authenticated user's own account.
+    #[serde(default)]
+    pub source: Option<AccountSource>,
+}
+
+/// A name-value pair of profile metadata.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Field {
+    /// The key of the field.
+    pub name: String,
+    /// The value of the field, as HTML.
+    pub value: String,
+    /// When the link in the value was verified with `rel="me"` (ISO 8601), if it was.
+    #[serde(default)]
+    pub verified_at: Option<String>,
+}
+
+/// A role shown on an account's profile.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AccountRole {
+    /// The ID of the role.
+    pub id: String,
+    /// The name of the role.
+    pub name: String,
+    /// The hex color of the role badge, or an empty string if it has none.
+    #[serde(default)]
+    pub color: String,
+}
+
+/// Profile data of the authenticated user, as entered when editing the profile.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AccountSource {
+    /// The bio as plain text.
+    pub note: String,
+    /// The profile fields, with values as plain text.
+    #[serde(default)]
+    pub fields: Vec<Field>,
+    /// The default visibility of new statuses ("public", "unlisted", "private" or "direct").
+    pub privacy: String,
+    /// Whether new statuses are marked as sensitive by default.
+    pub sensitive: bool,
+    /// The default language of new statuses (ISO 639-1), if set.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// The number of pending follow requests.
+    #[serde(default)]
+    pub follow_requests_count: u64,
+    /// Whether the user hides their followers and follows.
+    #[serde(default)]
+    pub hide_collections: Option<bool>,
+    /// Whether the user has opted into discovery features.
+    #[serde(default)]
+    pub discoverable: Option<bool>,
+    /// Whether public statuses may be indexed for full-text search.
+    #[serde(default)]
+    pub indexable: Option<bool>,
 }
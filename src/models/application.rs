@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Represents an application that interfaces with the REST API.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Application {
+    /// The name of the application.
+    pub name: String,
+    /// The website associated with the application.
+    #[serde(default)]
+    pub website: Option<String>,
+    /// The scopes the application was registered with.
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// The registered redirect URIs of the application.
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+    /// Used for Web Push API subscriptions (deprecated since Mastodon 4.3.0).
+    #[serde(default)]
+    pub vapid_key: Option<String>,
+}
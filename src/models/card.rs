@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// Represents a rich preview card generated from a link in a status.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PreviewCard {
+    /// Location of the linked resource.
+    pub url: String,
+    /// Title of the linked resource.
+    pub title: String,
+    /// Description of the preview.
+    #[serde(default)]
+    pub description: String,
+    /// The type of the preview card ("link", "photo", "video" or "rich").
+    #[serde(rename = "type")]
+    pub card_type: String,
+    /// The author of the original resource.
+    #[serde(default)]
+    pub author_name: String,
+    /// A link to the author of the original resource.
+    #[serde(default)]
+    pub author_url: String,
+    /// The provider of the original resource.
+    #[serde(default)]
+    pub provider_name: String,
+    /// A link to the provider of the original resource.
+    #[serde(default)]
+    pub provider_url: String,
+    /// HTML to be used for generating the preview card.
+    #[serde(default)]
+    pub html: String,
+    /// Width of the preview, in pixels.
+    #[serde(default)]
+    pub width: u32,
+    /// Height of the preview, in pixels.
+    #[serde(default)]
+    pub height: u32,
+    /// Preview thumbnail.
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Used for photo embeds, instead of custom `html`.
+    #[serde(default)]
+    pub embed_url: String,
+    /// A hash computed by the BlurHash algorithm, for generating colorful preview thumbnails.
+    #[serde(default)]
+    pub blurhash: Option<String>,
+}
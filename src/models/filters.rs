@@ -10,6 +10,34 @@ pub struct Filter {
     pub whole_word: bool,
 }
 
+/// A filter (v2 API) that matched a status, as reported in [`Status::filtered`](crate::models::Status::filtered).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FilterResult {
+    /// The filter that was matched.
+    pub filter: StatusFilter,
+    /// The keywords within the filter that were matched.
+    #[serde(default)]
+    pub keyword_matches: Option<Vec<String>>,
+    /// The status IDs within the filter that were matched.
+    #[serde(default)]
+    pub status_matches: Option<Vec<String>>,
+}
+
+/// A user-defined filter (v2 API) for hiding statuses.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StatusFilter {
+    /// The ID of the filter.
+    pub id: String,
+    /// A title given by the user to name the filter.
+    pub title: String,
+    /// The contexts in which the filter should be applied ("home", "notifications", ...).
+    pub context: Vec<String>,
+    /// When the filter should no longer be applied (ISO 8601).
+    pub expires_at: Option<String>,
+    /// The action to take on a matching status ("warn", "hide" or "blur").
+    pub filter_action: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct List {
     pub id: String,
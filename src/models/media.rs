@@ -5,7 +5,7 @@ pub struct MediaAttachment {
     pub id: String,
     #[serde(rename = "type")]
     pub media_type: String,
-    pub url: String,
+    pub url: Option<String>,
     pub preview_url: Option<String>,
     pub remote_url: Option<String>,
     pub description: Option<String>,
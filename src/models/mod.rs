@@ -1,5 +1,7 @@
 pub mod account;
 pub mod announcement;
+pub mod application;
+pub mod card;
 pub mod conversations;
 pub mod extras;
 pub mod filters;
@@ -15,11 +17,13 @@ pub mod status;
 pub mod suggestion;
 pub mod tag;
 
-pub use account::Account;
+pub use account::{Account, AccountRole, AccountSource, Field};
 pub use announcement::{Announcement, AnnouncementReaction};
+pub use application::Application;
+pub use card::PreviewCard;
 pub use conversations::Conversation;
 pub use extras::{Mention, Notification, Relationship, Tag, TagHistory};
-pub use filters::{Filter, List};
+pub use filters::{Filter, FilterResult, List, StatusFilter};
 pub use instance::{Activity, Instance, Rule};
 pub use marker::Marker;
 pub use media::MediaAttachment;
@@ -1,4 +1,7 @@
-use crate::models::Account;
+use crate::models::{
+    Account, Application, CustomEmoji, FilterResult, MediaAttachment, Mention, Poll, PreviewCard,
+    Tag,
+};
 use serde::{Deserialize, Serialize};
 
 /// Represents a status (post) on Mastodon.
@@ -34,6 +37,58 @@ pub struct Status {
     pub content: String,
     /// The account that created the status.
     pub account: Account,
+    /// Media attached to the status.
+    #[serde(default)]
+    pub media_attachments: Vec<MediaAttachment>,
+    /// Mentions of users within the status content.
+    #[serde(default)]
+    pub mentions: Vec<Mention>,
+    /// Hashtags used within the status content.
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    /// Custom emoji to be used when rendering the status content.
+    #[serde(default)]
+    pub emojis: Vec<CustomEmoji>,
+    /// The poll attached to the status, if any.
+    #[serde(default)]
+    pub poll: Option<Poll>,
+    /// Preview card for links included within the status content.
+    #[serde(default)]
+    pub card: Option<PreviewCard>,
+    /// The status being reblogged (boosted), if this status is a reblog.
+    #[serde(default)]
+    pub reblog: Option<Box<Status>>,
+    /// The application used to post the status. Only shown for own statuses.
+    #[serde(default)]
+    pub application: Option<Application>,
+    /// The time the status was last edited (ISO 8601), if it was edited.
+    #[serde(default)]
+    pub edited_at: Option<String>,
+    /// Whether the authenticated user has favourited the status.
+    #[serde(default)]
+    pub favourited: Option<bool>,
+    /// Whether the authenticated user has reblogged the status.
+    #[serde(default)]
+    pub reblogged: Option<bool>,
+    /// Whether the authenticated user has muted the conversation of the status.
+    #[serde(default)]
+    pub muted: Option<bool>,
+    /// Whether the authenticated user has bookmarked the status.
+    #[serde(default)]
+    pub bookmarked: Option<bool>,
+    /// Whether the authenticated user has pinned the status. Only shown for own statuses.
+    #[serde(default)]
+    pub pinned: Option<bool>,
+    /// The filters of the authenticated user that matched the status.
+    #[serde(default)]
+    pub filtered: Vec<FilterResult>,
+}
+
+impl Status {
+    /// Returns the reblogged status for reblogs, or the status itself otherwise.
+    pub fn original(&self) -> &Status {
+        self.reblog.as_deref().unwrap_or(self)
+    }
 }
 
 /// Represents the context of a status (ancestors and descendants).
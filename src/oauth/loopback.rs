@@ -0,0 +1,316 @@
+//! Interactive login through a temporary HTTP listener on the loopback interface.
+//!
+//! Instead of asking the user to copy an out-of-band code, [`LoopbackLogin`] registers
+//! the app with a `http://127.0.0.1:<port>/callback` redirect URI and captures the
+//! authorization code when the browser is redirected back.
+
+use super::{AuthorizationRequest, OAuth, Token};
+use crate::MastodonClient;
+use crate::error::{MastodonError, Result};
+use crate::methods::apps::{AppRegistration, RegisterAppParams};
+use futures_util::StreamExt;
+use futures_util::stream::FuturesUnordered;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use url::Url;
+
+/// Path on the listener the instance redirects to.
+const CALLBACK_PATH: &str = "/callback";
+
+/// Largest request head read from the browser.
+const MAX_REQUEST_SIZE: usize = 16 * 1024;
+
+/// How long a connection may take to send its request head before it is dropped.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Builder for an interactive login through a loopback redirect.
+///
+/// # Example
+/// ```no_run
+/// use mastodon_api::MastodonClient;
+/// use mastodon_api::oauth::LoopbackLogin;
+///
+/// # #[tokio::main]
+/// # async fn main() -> mastodon_api::Result<()> {
+/// let client = MastodonClient::new("https://mastodon.social");
+/// let login = LoopbackLogin::new(client, "My CLI")
+///     .scopes("read write")
+///     .start()
+///     .await?;
+///
+/// println!("Open this URL to log in: {}", login.authorize_url());
+/// let (client, token) = login.finish().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct LoopbackLogin {
+    client: MastodonClient,
+    client_name: String,
+    scopes: String,
+    website: Option<String>,
+    port: u16,
+    force_login: bool,
+    timeout: Duration,
+    app: Option<AppRegistration>,
+}
+
+impl LoopbackLogin {
+    /// Creates a login that registers an app named `client_name`.
+    pub fn new(client: MastodonClient, client_name: &str) -> Self {
+        Self {
+            client,
+            client_name: client_name.to_string(),
+            scopes: "read".to_string(),
+            website: None,
+            port: 0,
+            force_login: false,
+            timeout: Duration::from_secs(300),
+            app: None,
+        }
+    }
+
+    /// Sets the space-separated scopes to request. Defaults to `read`.
+    pub fn scopes(mut self, scopes: &str) -> Self {
+        self.scopes = scopes.to_string();
+        self
+    }
+
+    /// Sets the website of the app shown on the authorization page.
+    pub fn website(mut self, website: &str) -> Self {
+        self.website = Some(website.to_string());
+        self
+    }
+
+    /// Sets the port to listen on. Defaults to `0`, picking a free port.
+    ///
+    /// A fixed port is needed to reuse an app registered by an earlier login, as the
+    /// redirect URI must match the registered one exactly.
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Forces the user to log in again, e.g. to authorize a different account.
+    pub fn force_login(mut self, force: bool) -> Self {
+        self.force_login = force;
+        self
+    }
+
+    /// Sets how long [`PendingLogin::finish`] waits for the redirect. Defaults to 5 minutes.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Reuses an app registered earlier instead of registering a new one.
+    pub fn app(mut self, app: AppRegistration) -> Self {
+        self.app = Some(app);
+        self
+    }
+
+    /// Starts the listener, registers the app and builds the authorization URL.
+    pub async fn start(self) -> Result<PendingLogin> {
+        let listener = TcpListener::bind(("127.0.0.1", self.port))
+            .await
+            .map_err(|err| MastodonError::Custom(format!("Loopback listener: {}", err)))?;
+        let port = listener
+            .local_addr()
+            .map_err(|err| MastodonError::Custom(format!("Loopback listener: {}", err)))?
+            .port();
+        let redirect_uri = format!("http://127.0.0.1:{}{}", port, CALLBACK_PATH);
+
+        let app = match self.app {
+            Some(app) => app,
+            None => {
+                self.client
+                    .apps()
+                    .register(&RegisterAppParams {
+                        client_name: self.client_name,
+                        redirect_uris: redirect_uri.clone(),
+                        scopes: self.scopes.clone(),
+                        website: self.website,
+                    })
+                    .await?
+            }
+        };
+
+        let oauth = OAuth::new(
+            self.client,
+            &app.client_id,
+            &app.client_secret,
+            &redirect_uri,
+        )
+        .scopes(&self.scopes)
+        .force_login(self.force_login);
+        let request = oauth.authorize_url()?;
+
+        Ok(PendingLogin {
+            oauth,
+            app,
+            request,
+            listener,
+            timeout: self.timeout,
+        })
+    }
+}
+
+/// A login waiting for the user to authorize the app in the browser.
+pub struct PendingLogin {
+    oauth: OAuth,
+    app: AppRegistration,
+    request: AuthorizationRequest,
+    listener: TcpListener,
+    timeout: Duration,
+}
+
+impl PendingLogin {
+    /// Returns the URL to open in the user's browser.
+    pub fn authorize_url(&self) -> &Url {
+        &self.request.url
+    }
+
+    /// Returns the redirect URI the listener answers on.
+    pub fn redirect_uri(&self) -> &str {
+        self.oauth.redirect_uri()
+    }
+
+    /// Returns the registered app, e.g. to store its credentials for later logins.
+    pub fn app(&self) -> &AppRegistration {
+        &self.app
+    }
+
+    /// Returns the OAuth helper for the app, e.g. to refresh or revoke tokens later.
+    pub fn oauth(&self) -> &OAuth {
+        &self.oauth
+    }
+
+    /// Waits for the redirect, validates its `state` and exchanges the code for a token.
+    ///
+    /// Requests to the callback with a foreign `state` or without a code are answered
+    /// with an error page and otherwise ignored. Fails with [`MastodonError::AuthError`]
+    /// if the user denies access or no redirect arrives before the timeout.
+    pub async fn finish(self) -> Result<(MastodonClient, Token)> {
+        let code = tokio::time::timeout(self.timeout, self.wait_for_code())
+            .await
+            .map_err(|_| MastodonError::AuthError {
+                message: "timed out waiting for the authorization redirect".into(),
+                response: None,
+            })??;
+        self.oauth
+            .authenticate(&code, &self.request.code_verifier)
+            .await
+    }
+
+    async fn wait_for_code(&self) -> Result<String> {
+        // Connections are read concurrently, so one left idle by the browser (e.g. a
+        // preconnect) cannot hold up the redirect.
+        let mut reads = FuturesUnordered::new();
+        loop {
+            let (mut stream, query) = tokio::select! {
+                accepted = self.listener.accept() => {
+                    let (mut stream, _) = accepted.map_err(|err| {
+                        MastodonError::Custom(format!("Loopback listener: {}", err))
+                    })?;
+                    reads.push(async move {
+                        let query =
+                            tokio::time::timeout(READ_TIMEOUT, read_callback(&mut stream)).await;
+                        (stream, query)
+                    });
+                    continue;
+                }
+                Some(read) = reads.next(), if !reads.is_empty() => read,
+            };
+            let Ok(query) = query else {
+                continue;
+            };
+
+            // Browsers also ask for things like `/favicon.ico`; only the callback counts.
+            let Some(query) = query else {
+                respond(&mut stream, "404 Not Found", "Not found.").await;
+                continue;
+            };
+
+            if let Some(error) = query.get("error") {
+                respond(&mut stream, "400 Bad Request", "Authorization was denied.").await;
+                let description = query.get("error_description").unwrap_or(error);
+                return Err(MastodonError::AuthError {
+                    message: format!("authorization denied: {}", description),
+                    response: None,
+                });
+            }
+            // Stray requests, e.g. from a reloaded old tab, must not end the login.
+            if query.get("state") != Some(&self.request.state) {
+                respond(&mut stream, "400 Bad Request", "Invalid state.").await;
+                continue;
+            }
+            let Some(code) = query.get("code") else {
+                respond(
+                    &mut stream,
+                    "400 Bad Request",
+                    "Missing authorization code.",
+                )
+                .await;
+                continue;
+            };
+
+            respond(
+                &mut stream,
+                "200 OK",
+                "Login complete. You can close this window.",
+            )
+            .await;
+            return Ok(code.clone());
+        }
+    }
+}
+
+impl std::fmt::Debug for PendingLogin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingLogin")
+            .field("authorize_url", &self.request.url.as_str())
+            .field("redirect_uri", &self.redirect_uri())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Reads the request head and returns the query of a request for the callback path.
+async fn read_callback(stream: &mut TcpStream) -> Option<HashMap<String, String>> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buf).await.ok()?;
+        if read == 0 || head.len() + read > MAX_REQUEST_SIZE {
+            break;
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next()?.split_whitespace();
+    if request_line.next()? != "GET" {
+        return None;
+    }
+    let target = Url::parse(&format!("http://127.0.0.1{}", request_line.next()?)).ok()?;
+    if target.path() != CALLBACK_PATH {
+        return None;
+    }
+    Some(target.query_pairs().into_owned().collect())
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, message: &str) {
+    let body = format!(
+        "<!DOCTYPE html><html><body><p>{}</p></body></html>",
+        message
+    );
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    // The browser may already have gone away; the result is decided by the request.
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
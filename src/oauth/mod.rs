@@ -0,0 +1,374 @@
+//! OAuth 2.0 authorization code flow with PKCE.
+//!
+//! After registering an application with [`AppsHandler::register`](crate::methods::apps::AppsHandler::register),
+//! an [`OAuth`] helper turns the app credentials into user access tokens:
+//!
+//! 1. [`OAuth::authorize_url`] builds the URL the user opens in a browser, along with
+//!    the `state` and PKCE code verifier to keep for the next step.
+//! 2. The instance redirects back with a `code`, which [`OAuth::authenticate`] exchanges
+//!    for a token and an authenticated [`MastodonClient`].
+//! 3. Tokens can later be refreshed with [`OAuth::refresh`] and revoked with [`OAuth::revoke`].
+//!
+//! Services that only need public data can skip the user entirely and obtain an app
+//! token with [`OAuth::authenticate_app`]. Command-line tools can let [`LoopbackLogin`]
+//! register the app and capture the redirect instead of asking for an out-of-band code.
+
+pub mod loopback;
+
+pub use loopback::{LoopbackLogin, PendingLogin};
+
+use crate::dry_run::DryRunLog;
+use crate::error::Result;
+use crate::methods::apps::AppRegistration;
+use crate::scopes::Scopes;
+use crate::{MastodonClient, ResponseCache};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+/// Redirect URI for apps that display the authorization code instead of redirecting.
+pub const OUT_OF_BAND: &str = "urn:ietf:wg:oauth:2.0:oob";
+
+/// An access token issued by `/oauth/token`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Token {
+    /// The access token, used as a bearer token for API requests.
+    pub access_token: String,
+    /// The token type, always `Bearer`.
+    pub token_type: String,
+    /// Space-separated scopes granted to the token.
+    pub scope: String,
+    /// When the token was created, as a Unix timestamp.
+    pub created_at: i64,
+    /// Token for obtaining a new access token, if the server issues refresh tokens.
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Seconds until the access token expires, if it expires at all.
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+}
+
+/// An authorization URL and the secrets needed to complete the flow.
+///
+/// `state` and `code_verifier` must be kept until the user is redirected back; they
+/// are not sent to the instance until the code is exchanged.
+#[derive(Debug, Clone)]
+pub struct AuthorizationRequest {
+    /// The URL to open in the user's browser.
+    pub url: Url,
+    /// Random value the instance echoes back in the redirect, to be compared by the app.
+    pub state: String,
+    /// The PKCE code verifier, passed to [`OAuth::authenticate`] with the code.
+    pub code_verifier: String,
+}
+
+impl Token {
+    /// Returns the scopes granted to the token.
+    ///
+    /// Returns `None` if no scope is listed or if any of them is unknown to this crate,
+    /// as the token's permissions cannot be checked then.
+    pub fn scopes(&self) -> Option<Scopes> {
+        self.scope
+            .parse()
+            .ok()
+            .filter(|scopes: &Scopes| !scopes.is_empty())
+    }
+}
+
+#[derive(Serialize)]
+struct TokenParams<'a> {
+    grant_type: &'a str,
+    client_id: &'a str,
+    client_secret: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    redirect_uri: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code_verifier: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct RevokeParams<'a> {
+    client_id: &'a str,
+    client_secret: &'a str,
+    token: &'a str,
+}
+
+/// Helper for obtaining user tokens for a registered application.
+///
+/// # Example
+/// ```no_run
+/// use mastodon_api::MastodonClient;
+/// use mastodon_api::methods::apps::RegisterAppParams;
+/// use mastodon_api::oauth::{OAuth, OUT_OF_BAND};
+///
+/// # #[tokio::main]
+/// # async fn main() -> mastodon_api::Result<()> {
+/// let client = MastodonClient::new("https://mastodon.social");
+/// let app = client
+///     .apps()
+///     .register(&RegisterAppParams {
+///         client_name: "My Bot".to_string(),
+///         redirect_uris: OUT_OF_BAND.to_string(),
+///         scopes: "read write".to_string(),
+///         website: None,
+///     })
+///     .await?;
+///
+/// let oauth = OAuth::from_app(client, &app).scopes("read write");
+/// let request = oauth.authorize_url()?;
+/// println!("Open {} and paste the code:", request.url);
+///
+/// let mut code = String::new();
+/// std::io::stdin().read_line(&mut code).unwrap();
+/// let (client, token) = oauth.authenticate(code.trim(), &request.code_verifier).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct OAuth {
+    client: MastodonClient,
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+    scopes: String,
+    force_login: bool,
+    lang: Option<String>,
+}
+
+impl OAuth {
+    /// Creates a helper for the given app credentials.
+    ///
+    /// `client` is used to reach the instance; its access token, if any, is ignored.
+    pub fn new(
+        client: MastodonClient,
+        client_id: &str,
+        client_secret: &str,
+        redirect_uri: &str,
+    ) -> Self {
+        Self {
+            client,
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            redirect_uri: redirect_uri.to_string(),
+            scopes: "read".to_string(),
+            force_login: false,
+            lang: None,
+        }
+    }
+
+    /// Creates a helper for an application returned by `apps().register`.
+    pub fn from_app(client: MastodonClient, app: &AppRegistration) -> Self {
+        Self::new(
+            client,
+            &app.client_id,
+            &app.client_secret,
+            &app.redirect_uri,
+        )
+    }
+
+    /// Sets the space-separated scopes to request. Defaults to `read`.
+    ///
+    /// They must be a subset of the scopes the app was registered with.
+    pub fn scopes(mut self, scopes: &str) -> Self {
+        self.scopes = scopes.to_string();
+        self
+    }
+
+    /// Forces the user to log in again, e.g. to authorize a different account.
+    pub fn force_login(mut self, force: bool) -> Self {
+        self.force_login = force;
+        self
+    }
+
+    /// Sets the ISO 639-1 language of the authorization page.
+    pub fn lang(mut self, lang: &str) -> Self {
+        self.lang = Some(lang.to_string());
+        self
+    }
+
+    /// Returns the redirect URI the helper was created with.
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    /// Builds the authorization URL with a random `state` and PKCE challenge.
+    pub fn authorize_url(&self) -> Result<AuthorizationRequest> {
+        self.authorize_url_with_state(&random_token())
+    }
+
+    /// Builds the authorization URL with the given `state` and a random PKCE challenge.
+    ///
+    /// Corresponds to `GET /oauth/authorize`.
+    pub fn authorize_url_with_state(&self, state: &str) -> Result<AuthorizationRequest> {
+        let code_verifier = random_token();
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+
+        let mut url = Url::parse(&format!("{}/oauth/authorize", self.client.base_url()))?;
+        {
+            let mut query = url.query_pairs_mut();
+            query
+                .append_pair("response_type", "code")
+                .append_pair("client_id", &self.client_id)
+                .append_pair("redirect_uri", &self.redirect_uri)
+                .append_pair("scope", &self.scopes)
+                .append_pair("state", state)
+                .append_pair("code_challenge", &challenge)
+                .append_pair("code_challenge_method", "S256");
+            if self.force_login {
+                query.append_pair("force_login", "true");
+            }
+            if let Some(lang) = &self.lang {
+                query.append_pair("lang", lang);
+            }
+        }
+
+        Ok(AuthorizationRequest {
+            url,
+            state: state.to_string(),
+            code_verifier,
+        })
+    }
+
+    /// Exchanges an authorization code for a token.
+    ///
+    /// Parameters:
+    /// - `code`: The code the instance redirected back with.
+    /// - `code_verifier`: The verifier from the matching [`AuthorizationRequest`].
+    ///
+    /// Returns:
+    /// - `Result<Token>`: The issued token.
+    ///
+    /// Corresponds to `POST /oauth/token` with `grant_type=authorization_code`.
+    pub async fn exchange_code(&self, code: &str, code_verifier: &str) -> Result<Token> {
+        self.request_token(&TokenParams {
+            grant_type: "authorization_code",
+            client_id: &self.client_id,
+            client_secret: &self.client_secret,
+            redirect_uri: Some(&self.redirect_uri),
+            code: Some(code),
+            code_verifier: Some(code_verifier),
+            refresh_token: None,
+            scope: Some(&self.scopes),
+        })
+        .await
+    }
+
+    /// Exchanges an authorization code and returns a client authenticated with the token.
+    ///
+    /// The returned client shares the configuration of the client the helper was created with.
+    pub async fn authenticate(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<(MastodonClient, Token)> {
+        let token = self.exchange_code(code, code_verifier).await?;
+        Ok((self.client_for(&token), token))
+    }
+
+    /// Obtains an app token, which acts on behalf of the application rather than a user.
+    ///
+    /// App tokens can read public data and verify the app's credentials, but cannot
+    /// access account-specific endpoints.
+    ///
+    /// Corresponds to `POST /oauth/token` with `grant_type=client_credentials`.
+    pub async fn client_credentials(&self) -> Result<Token> {
+        self.request_token(&TokenParams {
+            grant_type: "client_credentials",
+            client_id: &self.client_id,
+            client_secret: &self.client_secret,
+            redirect_uri: Some(&self.redirect_uri),
+            code: None,
+            code_verifier: None,
+            refresh_token: None,
+            scope: Some(&self.scopes),
+        })
+        .await
+    }
+
+    /// Obtains an app token and returns a client authenticated with it.
+    pub async fn authenticate_app(&self) -> Result<(MastodonClient, Token)> {
+        let token = self.client_credentials().await?;
+        Ok((self.client_for(&token), token))
+    }
+
+    /// Obtains a new token using a refresh token.
+    ///
+    /// Corresponds to `POST /oauth/token` with `grant_type=refresh_token`.
+    pub async fn refresh(&self, refresh_token: &str) -> Result<Token> {
+        self.request_token(&TokenParams {
+            grant_type: "refresh_token",
+            client_id: &self.client_id,
+            client_secret: &self.client_secret,
+            redirect_uri: None,
+            code: None,
+            code_verifier: None,
+            refresh_token: Some(refresh_token),
+            scope: None,
+        })
+        .await
+    }
+
+    /// Revokes an access token, logging the app out of the account.
+    ///
+    /// Corresponds to `POST /oauth/revoke`.
+    pub async fn revoke(&self, token: &str) -> Result<()> {
+        let url = format!("{}/oauth/revoke", self.client.base_url());
+        let req = self.client.http_client().post(&url).form(&RevokeParams {
+            client_id: &self.client_id,
+            client_secret: &self.client_secret,
+            token,
+        });
+        self.unauthenticated().send_no_content(req).await
+    }
+
+    /// Returns a client authenticated with the given token, with its scopes recorded if
+    /// they are all known.
+    ///
+    /// The client has its own rate limiter, response cache and dry-run log, as their
+    /// state belongs to a single token.
+    pub fn client_for(&self, token: &Token) -> MastodonClient {
+        let mut client = self.client.clone().with_token(&token.access_token);
+        client.scopes = token.scopes();
+        client.rate_limiter = client.rate_limiter.unshared();
+        client.cache = client.cache.as_ref().map(ResponseCache::unshared);
+        client.dry_run_log = DryRunLog::new();
+        client
+    }
+
+    async fn request_token(&self, params: &TokenParams<'_>) -> Result<Token> {
+        let url = format!("{}/oauth/token", self.client.base_url());
+        let req = self.client.http_client().post(&url).form(params);
+        self.unauthenticated().send(req).await
+    }
+
+    /// The client without an access token, so token requests never carry a stale one.
+    fn unauthenticated(&self) -> MastodonClient {
+        let mut client = self.client.clone();
+        client.access_token = None;
+        client
+    }
+}
+
+impl std::fmt::Debug for OAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuth")
+            .field("base_url", &self.client.base_url())
+            .field("client_id", &self.client_id)
+            .field("redirect_uri", &self.redirect_uri)
+            .field("scopes", &self.scopes)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns 32 random bytes, base64url-encoded (43 characters, as PKCE requires).
+fn random_token() -> String {
+    URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
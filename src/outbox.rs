@@ -0,0 +1,381 @@
+//! A durable queue of statuses waiting to be posted.
+//!
+//! An [`Outbox`] persists statuses to a local JSON file before they are sent, so
+//! posts survive the instance being unreachable and the bot being restarted. Each
+//! entry keeps its own `Idempotency-Key`, so an entry that was posted but whose
+//! response got lost is not posted twice when it is delivered again.
+//!
+//! Entries are delivered in the order they were queued. Replies can refer to an
+//! entry that has not been posted yet; they are sent once their parent has been
+//! posted, with `in_reply_to_id` pointing at the new status, which keeps threads
+//! intact.
+
+use crate::MastodonClient;
+use crate::error::{MastodonError, Result};
+use crate::methods::statuses::CreateStatusParams;
+use crate::models::Status;
+use crate::retry::idempotency_key;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// A status waiting in the [`Outbox`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    /// Local ID of the entry, assigned when it is queued.
+    pub id: u64,
+    /// The status to post. Media attachments must already be uploaded.
+    pub params: CreateStatusParams,
+    /// Entry whose status this one replies to, if it was queued as a reply.
+    pub reply_to_entry: Option<u64>,
+    /// `Idempotency-Key` sent with every delivery attempt of this entry.
+    pub idempotency_key: String,
+    /// Number of failed delivery attempts so far.
+    pub attempts: u32,
+    /// Error of the last failed delivery attempt.
+    pub last_error: Option<String>,
+}
+
+/// A status that was posted by [`Outbox::flush`].
+#[derive(Debug, Clone)]
+pub struct Delivered {
+    /// Local ID of the entry.
+    pub entry_id: u64,
+    /// The posted status.
+    pub status: Status,
+}
+
+/// An entry that was dropped from the [`Outbox`] because it cannot be delivered.
+#[derive(Debug)]
+pub struct Failed {
+    /// The dropped entry.
+    pub entry: OutboxEntry,
+    /// The error that caused the entry to be dropped.
+    pub error: MastodonError,
+}
+
+/// Results of delivering the entries of an [`Outbox`].
+#[derive(Debug, Default)]
+pub struct OutboxReport {
+    /// Entries that were posted, in order.
+    pub delivered: Vec<Delivered>,
+    /// Entries that were dropped, e.g. because the server rejected them.
+    pub failed: Vec<Failed>,
+    /// Number of entries still waiting to be delivered.
+    pub pending: usize,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct State {
+    next_id: u64,
+    entries: Vec<OutboxEntry>,
+    /// Status IDs of delivered entries that queued replies still refer to.
+    delivered: HashMap<u64, String>,
+}
+
+/// A file-backed queue of statuses, delivered in order with retries.
+///
+/// # Example
+/// ```no_run
+/// use mastodon_api::MastodonClient;
+/// use mastodon_api::methods::statuses::CreateStatusParams;
+/// use mastodon_api::outbox::Outbox;
+///
+/// # #[tokio::main]
+/// # async fn main() -> mastodon_api::Result<()> {
+/// let client = MastodonClient::new("https://mastodon.social").with_token("your_token");
+/// let outbox = Outbox::open("outbox.json")?;
+///
+/// let first = outbox.enqueue(CreateStatusParams {
+///     status: "A thread 🧵 1/2".to_string(),
+///     ..Default::default()
+/// })?;
+/// outbox.enqueue_reply(first, CreateStatusParams {
+///     status: "2/2".to_string(),
+///     ..Default::default()
+/// })?;
+///
+/// // Keeps retrying with backoff until both statuses are posted or rejected.
+/// let report = outbox.deliver(&client).await?;
+/// for failed in report.failed {
+///     eprintln!("Dropped entry {}: {}", failed.entry.id, failed.error);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Outbox {
+    path: PathBuf,
+    state: Mutex<State>,
+    gate: tokio::sync::Mutex<()>,
+    base_delay: Duration,
+    max_delay: Duration,
+    max_attempts: Option<u32>,
+}
+
+impl Outbox {
+    /// Opens the outbox stored at `path`, creating an empty one if the file does not exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let state = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => State::default(),
+            Err(err) => return Err(io_error(&path, err)),
+        };
+        Ok(Self {
+            path,
+            state: Mutex::new(state),
+            gate: tokio::sync::Mutex::new(()),
+            base_delay: Duration::from_secs(5),
+            max_delay: Duration::from_secs(300),
+            max_attempts: None,
+        })
+    }
+
+    /// Delay before retrying after a failed delivery. It doubles with every
+    /// consecutive failure.
+    pub fn base_delay(mut self, value: Duration) -> Self {
+        self.base_delay = value;
+        self
+    }
+
+    /// Upper bound for the delay between two delivery rounds.
+    pub fn max_delay(mut self, value: Duration) -> Self {
+        self.max_delay = value;
+        self
+    }
+
+    /// Number of failed attempts after which an entry is dropped. Unlimited by default,
+    /// so entries wait for as long as the instance is unreachable.
+    pub fn max_attempts(mut self, value: u32) -> Self {
+        self.max_attempts = Some(value.max(1));
+        self
+    }
+
+    /// Queues a status and returns the ID of its entry.
+    pub fn enqueue(&self, params: CreateStatusParams) -> Result<u64> {
+        self.push(params, None)
+    }
+
+    /// Queues a status replying to the status of another entry.
+    ///
+    /// The reply is posted after its parent, with `in_reply_to_id` set to the ID of the
+    /// parent's status. If the parent is dropped, the reply is dropped as well. The
+    /// parent must still be queued; to reply to a status that was already posted,
+    /// set `in_reply_to_id` and use [`enqueue`](Self::enqueue).
+    pub fn enqueue_reply(&self, parent_entry: u64, params: CreateStatusParams) -> Result<u64> {
+        {
+            let state = self.state.lock().unwrap();
+            let known = state.delivered.contains_key(&parent_entry)
+                || state.entries.iter().any(|entry| entry.id == parent_entry);
+            if !known {
+                return Err(MastodonError::Custom(format!(
+                    "Outbox entry {} is not queued",
+                    parent_entry
+                )));
+            }
+        }
+        self.push(params, Some(parent_entry))
+    }
+
+    /// Returns the entries waiting to be delivered, in order.
+    pub fn pending(&self) -> Vec<OutboxEntry> {
+        self.state.lock().unwrap().entries.clone()
+    }
+
+    /// Returns the number of entries waiting to be delivered.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    /// Returns `true` if no entries are waiting to be delivered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Tries to deliver every pending entry once, in order.
+    ///
+    /// Delivery stops at the first entry failing with a temporary error (the instance
+    /// being unreachable, in maintenance or rate limiting), leaving it and all later
+    /// entries queued. Entries rejected by the server are dropped and reported.
+    ///
+    /// With a client in dry-run mode, the first entry is recorded in its
+    /// [`DryRunLog`](crate::dry_run::DryRunLog) and the outbox is left untouched.
+    pub async fn flush(&self, client: &MastodonClient) -> Result<OutboxReport> {
+        let _gate = self.gate.lock().await;
+        let mut report = OutboxReport::default();
+
+        while let Some(mut entry) = self.next_entry() {
+            if let Some(parent) = entry.reply_to_entry {
+                let status_id = self.state.lock().unwrap().delivered.get(&parent).cloned();
+                match status_id {
+                    Some(status_id) => entry.params.in_reply_to_id = Some(status_id),
+                    None => {
+                        // The parent has been dropped, so there is nothing to reply to.
+                        let error = MastodonError::Custom(format!(
+                            "Parent entry {} was not delivered",
+                            parent
+                        ));
+                        report.failed.push(self.drop_entry(entry, error)?);
+                        continue;
+                    }
+                }
+            }
+
+            let result = client
+                .statuses()
+                .create_with_idempotency_key(&entry.params, &entry.idempotency_key)
+                .await;
+            match result {
+                Ok(status) => {
+                    self.complete(entry.id, &status.id)?;
+                    report.delivered.push(Delivered {
+                        entry_id: entry.id,
+                        status,
+                    });
+                }
+                // Nothing was sent, so the entry stays queued as it is.
+                Err(MastodonError::DryRun { .. }) => break,
+                Err(error) => {
+                    entry.attempts += 1;
+                    let exhausted = self.max_attempts.is_some_and(|max| entry.attempts >= max);
+                    if is_transient(&error) && !exhausted {
+                        self.record_failure(entry.id, &error)?;
+                        break;
+                    }
+                    report.failed.push(self.drop_entry(entry, error)?);
+                }
+            }
+        }
+
+        report.pending = self.len();
+        Ok(report)
+    }
+
+    /// Delivers all pending entries, waiting with exponential backoff between rounds
+    /// while the instance is unreachable.
+    ///
+    /// Returns once the outbox is empty, or after one round with a client in dry-run
+    /// mode. Wrap it in `tokio::time::timeout` to give up earlier; undelivered entries
+    /// stay in the file.
+    pub async fn deliver(&self, client: &MastodonClient) -> Result<OutboxReport> {
+        let mut report = OutboxReport::default();
+        let mut delay = self.base_delay;
+        loop {
+            let round = self.flush(client).await?;
+            let progress = !round.delivered.is_empty() || !round.failed.is_empty();
+            report.delivered.extend(round.delivered);
+            report.failed.extend(round.failed);
+            report.pending = round.pending;
+            if report.pending == 0 || client.is_dry_run() {
+                return Ok(report);
+            }
+
+            if progress {
+                delay = self.base_delay;
+            }
+            tokio::time::sleep(delay).await;
+            delay = delay.saturating_mul(2).min(self.max_delay);
+        }
+    }
+
+    fn push(&self, params: CreateStatusParams, reply_to_entry: Option<u64>) -> Result<u64> {
+        self.update(|state| {
+            let id = state.next_id;
+            state.next_id += 1;
+            state.entries.push(OutboxEntry {
+                id,
+                params,
+                reply_to_entry,
+                idempotency_key: idempotency_key(),
+                attempts: 0,
+                last_error: None,
+            });
+            id
+        })
+    }
+
+    fn next_entry(&self) -> Option<OutboxEntry> {
+        self.state.lock().unwrap().entries.first().cloned()
+    }
+
+    fn complete(&self, entry_id: u64, status_id: &str) -> Result<()> {
+        self.update(|state| {
+            state.entries.retain(|entry| entry.id != entry_id);
+            state.delivered.insert(entry_id, status_id.to_string());
+            prune_delivered(state);
+        })
+    }
+
+    fn record_failure(&self, entry_id: u64, error: &MastodonError) -> Result<()> {
+        self.update(|state| {
+            if let Some(entry) = state.entries.iter_mut().find(|entry| entry.id == entry_id) {
+                entry.attempts += 1;
+                entry.last_error = Some(error.to_string());
+            }
+        })
+    }
+
+    fn drop_entry(&self, mut entry: OutboxEntry, error: MastodonError) -> Result<Failed> {
+        self.update(|state| {
+            state.entries.retain(|queued| queued.id != entry.id);
+            prune_delivered(state);
+        })?;
+        entry.last_error = Some(error.to_string());
+        Ok(Failed { entry, error })
+    }
+
+    /// Applies a change, keeping the previous state if it cannot be saved, so the
+    /// queue in memory never differs from the file.
+    fn update<T>(&self, change: impl FnOnce(&mut State) -> T) -> Result<T> {
+        let mut state = self.state.lock().unwrap();
+        let mut changed = state.clone();
+        let value = change(&mut changed);
+        self.save(&changed)?;
+        *state = changed;
+        Ok(value)
+    }
+
+    /// Writes the state to a temporary file and moves it into place, so a crash
+    /// never leaves a truncated outbox behind.
+    fn save(&self, state: &State) -> Result<()> {
+        let data = serde_json::to_vec_pretty(state)?;
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, data).map_err(|err| io_error(&self.path, err))?;
+        std::fs::rename(&tmp, &self.path).map_err(|err| io_error(&self.path, err))
+    }
+}
+
+/// Forgets the status IDs of delivered entries no queued reply refers to anymore.
+fn prune_delivered(state: &mut State) {
+    let State {
+        entries, delivered, ..
+    } = state;
+    delivered.retain(|id, _| {
+        entries
+            .iter()
+            .any(|entry| entry.reply_to_entry == Some(*id))
+    });
+}
+
+/// Whether a delivery failed for a reason that may go away by itself.
+fn is_transient(error: &MastodonError) -> bool {
+    match error {
+        MastodonError::Reqwest(err) => err.status().is_none_or(|status| status.is_server_error()),
+        MastodonError::ServiceUnavailable { .. }
+        | MastodonError::RateLimited { .. }
+        | MastodonError::RateLimit(_) => true,
+        MastodonError::ApiError { status, .. } => {
+            status.is_server_error() || *status == StatusCode::REQUEST_TIMEOUT
+        }
+        _ => false,
+    }
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> MastodonError {
+    MastodonError::Custom(format!("Outbox file {}: {}", path.display(), err))
+}
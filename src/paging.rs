@@ -32,36 +32,26 @@ impl<'a, T: DeserializeOwned> PagedRequest<'a, T> {
             None => return Ok(None),
         };
 
-        let response = self.client.http_client().get(url);
-        let builder = response;
-
-        let mut req_builder = builder;
-        if let Some(token) = self.client.access_token() {
-            req_builder = req_builder.bearer_auth(token);
-        }
-
-        let resp = req_builder.send().await?;
-
-        if !resp.status().is_success() {
-            return Err(crate::error::MastodonError::ApiError {
-                status: resp.status(),
-                message: resp.text().await.unwrap_or_default(),
-            });
-        }
+        let req = self.client.http_client().get(url);
+        let resp = self.client.execute(req).await?;
 
         // Parse Link header
-        self.next_url = parse_link_header(resp.headers().get("Link"));
+        self.next_url = parse_link_header(resp.headers.get("Link"), "next");
 
-        Ok(Some(resp.json().await?))
+        Ok(Some(resp.json()?))
     }
 }
 
-/// Helper to parse the `Link` header into a URL string for the next page.
-fn parse_link_header(header: Option<&reqwest::header::HeaderValue>) -> Option<String> {
+/// Helper to parse the `Link` header into the URL with the given relation (`next` or `prev`).
+pub(crate) fn parse_link_header(
+    header: Option<&reqwest::header::HeaderValue>,
+    rel: &str,
+) -> Option<String> {
     let header_str = header?.to_str().ok()?;
+    let rel = format!("rel=\"{}\"", rel);
     // Example: <url>; rel="next", <url>; rel="prev"
     for part in header_str.split(',') {
-        if part.contains("rel=\"next\"") {
+        if part.contains(&rel) {
             let start = part.find('<')? + 1;
             let end = part.find('>')?;
             return Some(part[start..end].to_string());
@@ -0,0 +1,193 @@
+//! Managing several authenticated accounts at once.
+//!
+//! An [`AccountPool`] owns one [`MastodonClient`] per account, keyed by the account's
+//! handle (`bot@instance`). Accounts on the same instance share a connection pool,
+//! while each account keeps its own [`RateLimiter`](crate::RateLimiter), as Mastodon
+//! tracks rate limits per access token.
+
+use crate::MastodonClient;
+use crate::builder::MastodonClientBuilder;
+use crate::error::{MastodonError, Result};
+use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+
+type Configure = Box<dyn Fn(MastodonClientBuilder) -> MastodonClientBuilder + Send + Sync>;
+
+/// A set of authenticated clients, keyed by account handle.
+///
+/// Accounts can be added and removed at runtime from any task holding a reference
+/// to the pool; clients handed out by [`get`](Self::get) keep working after their
+/// account is removed. The connection pool of an instance is released once its last
+/// account is removed.
+///
+/// # Example
+/// ```
+/// use mastodon_api::pool::AccountPool;
+///
+/// # fn main() -> mastodon_api::Result<()> {
+/// let pool = AccountPool::new().configure(|builder| builder.user_agent("my-bots/1.0"));
+/// pool.add("news@mastodon.social", "token_a")?;
+/// pool.add("weather@mastodon.social", "token_b")?;
+/// pool.add("alerts@fosstodon.org", "token_c")?;
+///
+/// let client = pool.get("news@mastodon.social").unwrap();
+/// assert_eq!(client.base_url(), "https://mastodon.social");
+///
+/// pool.remove("alerts@fosstodon.org");
+/// assert_eq!(pool.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct AccountPool {
+    accounts: RwLock<HashMap<String, MastodonClient>>,
+    instances: Mutex<HashMap<String, Client>>,
+    configure: Option<Configure>,
+}
+
+impl AccountPool {
+    /// Creates a new, empty `AccountPool`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Customizes the builder of every client created by the pool, e.g. to set a user
+    /// agent, retry policy or middleware.
+    pub fn configure(
+        mut self,
+        f: impl Fn(MastodonClientBuilder) -> MastodonClientBuilder + Send + Sync + 'static,
+    ) -> Self {
+        self.configure = Some(Box::new(f));
+        self
+    }
+
+    /// Adds an account, connecting to `https://` followed by the domain of the handle.
+    ///
+    /// Parameters:
+    /// - `handle`: The handle of the account, e.g. `bot@mastodon.social`.
+    /// - `access_token`: The access token of the account.
+    ///
+    /// Returns:
+    /// - `Result<MastodonClient>`: The client of the account.
+    pub fn add(&self, handle: &str, access_token: &str) -> Result<MastodonClient> {
+        let handle = normalize(handle)?;
+        let domain = handle.split_once('@').map_or("", |(_, domain)| domain);
+        self.add_with_url(&handle, &format!("https://{}", domain), access_token)
+    }
+
+    /// Adds an account on an instance whose API is served from a different URL than
+    /// the domain of the handle.
+    ///
+    /// Replaces the client of an account that was already added.
+    pub fn add_with_url(
+        &self,
+        handle: &str,
+        instance_url: &str,
+        access_token: &str,
+    ) -> Result<MastodonClient> {
+        let handle = normalize(handle)?;
+        let instance_url = instance_url.trim_end_matches('/');
+
+        let mut builder = MastodonClient::builder(instance_url);
+        if let Some(configure) = &self.configure {
+            builder = configure(builder);
+        }
+        builder = builder.token(access_token);
+
+        let client = {
+            let mut instances = self.instances.lock().unwrap();
+            if let Some(http_client) = instances.get(instance_url) {
+                builder = builder.http_client(http_client.clone());
+            }
+            let client = builder.build()?;
+            instances
+                .entry(instance_url.to_string())
+                .or_insert_with(|| client.http_client().clone());
+            client
+        };
+
+        let replaced = self
+            .accounts
+            .write()
+            .unwrap()
+            .insert(handle, client.clone());
+        if let Some(replaced) = replaced {
+            self.release_instance(replaced.base_url());
+        }
+        Ok(client)
+    }
+
+    /// Adds an account with a client that was configured elsewhere.
+    ///
+    /// The client keeps its own connection pool and rate limiter.
+    pub fn insert(&self, handle: &str, client: MastodonClient) -> Result<()> {
+        let handle = normalize(handle)?;
+        let replaced = self.accounts.write().unwrap().insert(handle, client);
+        if let Some(replaced) = replaced {
+            self.release_instance(replaced.base_url());
+        }
+        Ok(())
+    }
+
+    /// Returns the client of the account with the given handle.
+    pub fn get(&self, handle: &str) -> Option<MastodonClient> {
+        let handle = normalize(handle).ok()?;
+        self.accounts.read().unwrap().get(&handle).cloned()
+    }
+
+    /// Removes an account, returning its client.
+    pub fn remove(&self, handle: &str) -> Option<MastodonClient> {
+        let handle = normalize(handle).ok()?;
+        let client = self.accounts.write().unwrap().remove(&handle)?;
+        self.release_instance(client.base_url());
+        Some(client)
+    }
+
+    /// Returns whether an account with the given handle is in the pool.
+    pub fn contains(&self, handle: &str) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// Returns the handles of all accounts, sorted alphabetically.
+    pub fn handles(&self) -> Vec<String> {
+        let mut handles: Vec<String> = self.accounts.read().unwrap().keys().cloned().collect();
+        handles.sort();
+        handles
+    }
+
+    /// Returns the number of accounts.
+    pub fn len(&self) -> usize {
+        self.accounts.read().unwrap().len()
+    }
+
+    /// Returns `true` if the pool has no accounts.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets the shared HTTP client of an instance no account uses anymore.
+    fn release_instance(&self, instance_url: &str) {
+        let accounts = self.accounts.read().unwrap();
+        if !accounts
+            .values()
+            .any(|client| client.base_url() == instance_url)
+        {
+            self.instances.lock().unwrap().remove(instance_url);
+        }
+    }
+}
+
+/// Normalizes a handle to `user@domain`, dropping a leading `@` and lowercasing the domain.
+fn normalize(handle: &str) -> Result<String> {
+    let handle = handle.trim().trim_start_matches('@');
+    match handle.split_once('@') {
+        Some((user, domain)) if !user.is_empty() && !domain.is_empty() => {
+            Ok(format!("{}@{}", user, domain.to_lowercase()))
+        }
+        _ => Err(MastodonError::Custom(format!(
+            "Invalid account handle '{}', expected user@instance",
+            handle
+        ))),
+    }
+}
@@ -0,0 +1,233 @@
+//! Client-side tracking of the rate limits reported by Mastodon.
+//!
+//! Every response carries `X-RateLimit-Limit`, `X-RateLimit-Remaining` and
+//! `X-RateLimit-Reset` headers. The [`RateLimiter`] records them and holds back
+//! outgoing requests once the budget is spent, sleeping until the reset
+//! timestamp announced by the server instead of running into a 429.
+
+use crate::error::{MastodonError, Result};
+use reqwest::header::HeaderMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A snapshot of the rate limit state reported by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Number of requests permitted per time period.
+    pub limit: u32,
+    /// Number of requests that can still be made in the current period.
+    pub remaining: u32,
+    /// When the current period ends and the budget is restored.
+    pub reset_at: Option<SystemTime>,
+}
+
+impl RateLimit {
+    /// Parses the `X-RateLimit-*` headers of a response.
+    ///
+    /// Returns `None` if the limit or remaining headers are missing or malformed.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let limit = header_str(headers, "X-RateLimit-Limit")?.parse().ok()?;
+        let remaining = header_str(headers, "X-RateLimit-Remaining")?.parse().ok()?;
+        let reset_at = header_str(headers, "X-RateLimit-Reset").and_then(parse_timestamp);
+        Some(Self {
+            limit,
+            remaining,
+            reset_at,
+        })
+    }
+
+    /// Returns how long to wait until the budget is restored, if the reset lies in the future.
+    pub fn time_until_reset(&self) -> Option<Duration> {
+        self.reset_at?.duration_since(SystemTime::now()).ok()
+    }
+}
+
+/// Governs outgoing requests according to the rate limit reported by the server.
+///
+/// Cloning a `RateLimiter` is cheap and all clones share the same budget, so one
+/// limiter covers every clone of a [`MastodonClient`](crate::MastodonClient) and
+/// every task using them. Once the remaining budget drops to the configured
+/// reserve, further requests are queued until the reset timestamp has passed.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    state: Arc<Mutex<Option<RateLimit>>>,
+    gate: Arc<tokio::sync::Mutex<()>>,
+    reserve: u32,
+    max_wait: Option<Duration>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RateLimiter {
+    /// Creates a new `RateLimiter` with no reserve and no upper bound on waiting.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(None)),
+            gate: Arc::new(tokio::sync::Mutex::new(())),
+            reserve: 0,
+            max_wait: None,
+        }
+    }
+
+    /// Returns a limiter with the same settings that does not share its state.
+    pub(crate) fn unshared(&self) -> Self {
+        Self {
+            reserve: self.reserve,
+            max_wait: self.max_wait,
+            ..Self::new()
+        }
+    }
+
+    /// Number of requests to keep in reserve.
+    ///
+    /// Requests are held back as soon as the remaining budget drops to this value,
+    /// leaving room for other consumers of the same token.
+    pub fn reserve(mut self, value: u32) -> Self {
+        self.reserve = value;
+        self
+    }
+
+    /// Longest time a request may be held back.
+    ///
+    /// If the budget is exhausted and the reset lies further in the future, the request
+    /// fails with [`MastodonError::RateLimit`] instead of waiting.
+    pub fn max_wait(mut self, value: Duration) -> Self {
+        self.max_wait = Some(value);
+        self
+    }
+
+    /// Returns the last rate limit state reported by the server, if any.
+    pub fn current(&self) -> Option<RateLimit> {
+        *self.state.lock().unwrap()
+    }
+
+    /// Records the rate limit headers of a response.
+    pub fn update(&self, headers: &HeaderMap) {
+        let Some(reported) = RateLimit::from_headers(headers) else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        *state = Some(match *state {
+            // Responses of concurrent requests may arrive out of order; within the same
+            // period the lowest remaining count is the most accurate one.
+            Some(known) if known.reset_at == reported.reset_at => RateLimit {
+                remaining: known.remaining.min(reported.remaining),
+                ..reported
+            },
+            _ => reported,
+        });
+    }
+
+    /// Marks the budget as spent until `reset_at`, e.g. after the server answered with a 429.
+    pub(crate) fn exhaust(&self, reset_at: SystemTime) {
+        let mut state = self.state.lock().unwrap();
+        let limit = state.map_or(1, |rl| rl.limit);
+        *state = Some(RateLimit {
+            limit,
+            remaining: 0,
+            reset_at: Some(reset_at),
+        });
+    }
+
+    /// Waits until the budget allows another request and reserves it.
+    pub(crate) async fn acquire(&self) -> Result<()> {
+        // Requests queue up on the gate while one of them is waiting for the reset.
+        let _gate = self.gate.lock().await;
+
+        let wait = {
+            let mut state = self.state.lock().unwrap();
+            let Some(rl) = state.as_mut() else {
+                return Ok(());
+            };
+            if rl.reset_at.is_some() && rl.time_until_reset().is_none() {
+                rl.remaining = rl.limit;
+            }
+            if rl.remaining > self.reserve {
+                rl.remaining -= 1;
+                return Ok(());
+            }
+            match rl.time_until_reset() {
+                Some(wait) => wait,
+                None => return Ok(()),
+            }
+        };
+
+        if let Some(max) = self.max_wait
+            && wait > max
+        {
+            return Err(MastodonError::RateLimit(format!("{}s", wait.as_secs())));
+        }
+
+        tokio::time::sleep(wait).await;
+
+        if let Some(rl) = self.state.lock().unwrap().as_mut() {
+            rl.remaining = rl.limit.saturating_sub(1);
+        }
+        Ok(())
+    }
+}
+
+fn header_str<'h>(headers: &'h HeaderMap, name: &str) -> Option<&'h str> {
+    headers.get(name)?.to_str().ok()
+}
+
+/// Parses an ISO 8601 timestamp such as `2017-12-20T22:30:00.418Z` or
+/// `2017-12-20T22:30:00+01:00`.
+pub(crate) fn parse_timestamp(value: &str) -> Option<SystemTime> {
+    let value = value.trim();
+    let (date, time) = value.split_once(['T', ' '])?;
+
+    let mut date_parts = date.splitn(3, '-');
+    let year: i64 = date_parts.next()?.parse().ok()?;
+    let month: i64 = date_parts.next()?.parse().ok()?;
+    let day: i64 = date_parts.next()?.parse().ok()?;
+
+    let (clock, offset_secs) = if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+        (clock, 0)
+    } else if let Some(idx) = time.rfind(['+', '-']) {
+        let (clock, offset) = time.split_at(idx);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let (hours, minutes) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+        let offset: i64 = hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60;
+        (clock, sign * offset)
+    } else {
+        (time, 0)
+    };
+
+    let (clock, fraction) = clock.split_once('.').unwrap_or((clock, ""));
+    let mut clock_parts = clock.splitn(3, ':');
+    let hour: i64 = clock_parts.next()?.parse().ok()?;
+    let minute: i64 = clock_parts.next()?.parse().ok()?;
+    let second: i64 = clock_parts.next()?.parse().ok()?;
+    let nanos: u32 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{:0<9}", &fraction[..fraction.len().min(9)])
+            .parse()
+            .ok()?
+    };
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let secs = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second
+        - offset_secs;
+    let secs = u64::try_from(secs).ok()?;
+    Some(UNIX_EPOCH + Duration::new(secs, nanos))
+}
+
+/// Number of days since 1970-01-01 for a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
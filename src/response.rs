@@ -0,0 +1,86 @@
+use crate::error::Result;
+use crate::paging::parse_link_header;
+use crate::rate_limit::RateLimit;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use url::Url;
+
+/// A fully received HTTP response, as seen by [`Middleware`](crate::middleware::Middleware).
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    /// Method of the request that produced this response.
+    pub method: Method,
+    /// URL of the request that produced this response.
+    pub url: Url,
+    /// Status code of the response.
+    pub status: StatusCode,
+    /// Headers of the response.
+    pub headers: HeaderMap,
+    /// Raw body of the response.
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Returns the body as text, replacing invalid UTF-8 sequences.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Deserializes the JSON body.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+}
+
+/// A deserialized response body together with the metadata of the response.
+///
+/// Returned by the `*_with_meta` methods, e.g. [`TimelinesHandler::home_with_meta`](crate::methods::timelines::TimelinesHandler::home_with_meta).
+#[derive(Debug, Clone)]
+pub struct Response<T> {
+    /// The deserialized body.
+    pub body: T,
+    /// Status code of the response.
+    pub status: StatusCode,
+    /// Headers of the response.
+    pub headers: HeaderMap,
+}
+
+impl<T> Response<T> {
+    /// Parses the response body of an `HttpResponse`.
+    pub(crate) fn from_http(response: HttpResponse) -> Result<Self>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(Self {
+            body: response.json()?,
+            status: response.status,
+            headers: response.headers,
+        })
+    }
+
+    /// Returns the URL of the next (older) page, from the `Link` header.
+    pub fn next_link(&self) -> Option<String> {
+        parse_link_header(self.headers.get("Link"), "next")
+    }
+
+    /// Returns the URL of the previous (newer) page, from the `Link` header.
+    pub fn prev_link(&self) -> Option<String> {
+        parse_link_header(self.headers.get("Link"), "prev")
+    }
+
+    /// Returns the rate limit state reported with the response.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        RateLimit::from_headers(&self.headers)
+    }
+
+    /// Returns the `X-Request-Id` assigned by the server, useful for support requests.
+    pub fn request_id(&self) -> Option<&str> {
+        self.headers.get("X-Request-Id")?.to_str().ok()
+    }
+
+    /// Discards the metadata and returns the body.
+    pub fn into_body(self) -> T {
+        self.body
+    }
+}
@@ -0,0 +1,185 @@
+//! Retry behaviour for failed requests.
+//!
+//! A [`RetryPolicy`] decides which failed requests are sent again and how long to
+//! wait in between. Non-idempotent requests (such as `POST`) are only retried when
+//! they carry an `Idempotency-Key` header, so a retry can never perform the same
+//! action twice on the server.
+
+use crate::error::MastodonError;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+use std::time::{Duration, SystemTime};
+
+/// Name of the header Mastodon uses to deduplicate repeated requests.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Controls when and how failed requests are retried.
+///
+/// # Example
+/// ```
+/// use mastodon_api::{MastodonClient, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::default()
+///     .max_attempts(5)
+///     .base_delay(Duration::from_millis(500));
+/// let client = MastodonClient::new("https://mastodon.social").with_retry_policy(policy);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    respect_retry_after: bool,
+    retry_methods: Vec<Method>,
+    retry_statuses: Vec<StatusCode>,
+}
+
+impl Default for RetryPolicy {
+    /// Up to 4 attempts with jittered exponential backoff starting at one second,
+    /// retrying idempotent methods on 429, 500, 502, 503 and 504.
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            respect_retry_after: true,
+            retry_methods: vec![
+                Method::GET,
+                Method::HEAD,
+                Method::PUT,
+                Method::DELETE,
+                Method::OPTIONS,
+            ],
+            retry_statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// Maximum number of attempts, including the first one.
+    pub fn max_attempts(mut self, value: u32) -> Self {
+        self.max_attempts = value.max(1);
+        self
+    }
+
+    /// Delay before the first retry. It doubles with every further attempt.
+    pub fn base_delay(mut self, value: Duration) -> Self {
+        self.base_delay = value;
+        self
+    }
+
+    /// Upper bound for the delay between two attempts.
+    ///
+    /// A `Retry-After` header asking for a longer wait is not shortened: the request
+    /// is not retried and the error is returned instead.
+    pub fn max_delay(mut self, value: Duration) -> Self {
+        self.max_delay = value;
+        self
+    }
+
+    /// Whether to randomize the backoff delay, so clients don't retry in lockstep.
+    pub fn jitter(mut self, value: bool) -> Self {
+        self.jitter = value;
+        self
+    }
+
+    /// Whether to wait for the duration given in a `Retry-After` response header.
+    pub fn respect_retry_after(mut self, value: bool) -> Self {
+        self.respect_retry_after = value;
+        self
+    }
+
+    /// HTTP methods that are safe to retry.
+    ///
+    /// Requests with any other method are only retried if they carry an
+    /// `Idempotency-Key` header.
+    pub fn retry_methods(mut self, value: Vec<Method>) -> Self {
+        self.retry_methods = value;
+        self
+    }
+
+    /// Response statuses that cause a request to be retried.
+    pub fn retry_statuses(mut self, value: Vec<StatusCode>) -> Self {
+        self.retry_statuses = value;
+        self
+    }
+
+    /// Returns the maximum number of attempts.
+    pub fn attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Whether a request may be sent more than once without side effects.
+    pub(crate) fn is_replayable(&self, method: &Method, headers: &HeaderMap) -> bool {
+        self.retry_methods.contains(method) || headers.contains_key(IDEMPOTENCY_KEY_HEADER)
+    }
+
+    /// Whether a response with the given status should be retried.
+    ///
+    /// Rate-limited requests were rejected before being processed, so they are
+    /// retried regardless of the method.
+    pub(crate) fn retries_status(&self, status: StatusCode, replayable: bool) -> bool {
+        self.retry_statuses.contains(&status)
+            && (replayable || status == StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    /// Whether a request that failed before a response arrived should be retried.
+    pub(crate) fn retries_error(&self, error: &MastodonError, replayable: bool) -> bool {
+        match error {
+            MastodonError::Reqwest(err) => replayable && (err.is_timeout() || err.is_connect()),
+            _ => false,
+        }
+    }
+
+    /// Delay before the given retry (1 for the first retry).
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        if self.jitter {
+            // Equal jitter: keep half of the delay and randomize the other half.
+            let half = delay / 2;
+            let spread = half.as_millis() as u64 + 1;
+            half + Duration::from_millis(rand::random_range(0..spread))
+        } else {
+            delay
+        }
+    }
+
+    /// Parses the `Retry-After` header, given either in seconds or as an HTTP date.
+    pub(crate) fn retry_after(&self, headers: &HeaderMap) -> Option<Duration> {
+        if !self.respect_retry_after {
+            return None;
+        }
+        let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+        if let Ok(secs) = value.trim().parse::<u64>() {
+            return Some(Duration::from_secs(secs));
+        }
+        let date = httpdate::parse_http_date(value).ok()?;
+        Some(date.duration_since(SystemTime::now()).unwrap_or_default())
+    }
+
+    /// Whether the `Retry-After` header asks for a longer wait than `max_delay`.
+    pub(crate) fn exceeds_max_delay(&self, headers: &HeaderMap) -> bool {
+        self.retry_after(headers)
+            .is_some_and(|wait| wait > self.max_delay)
+    }
+}
+
+/// Generates a random key for the `Idempotency-Key` header.
+pub fn idempotency_key() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
@@ -0,0 +1,447 @@
+//! Typed OAuth scopes.
+//!
+//! Mastodon grants tokens a set of scopes, either top-level (`read`, `write`) or
+//! granular (`read:statuses`, `write:media`, `admin:write:accounts`). Once the scopes
+//! granted to a token are known, [`MastodonClient::with_scopes`](crate::MastodonClient::with_scopes)
+//! records them and handlers fail with [`MastodonError::MissingScope`] before sending
+//! a request the token is not allowed to make, instead of with a 403 afterwards.
+
+use crate::error::{MastodonError, Result};
+use reqwest::Method;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// An OAuth scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Scope {
+    /// `read`: all granular `read:*` scopes.
+    Read,
+    /// `read:accounts`
+    ReadAccounts,
+    /// `read:blocks`
+    ReadBlocks,
+    /// `read:bookmarks`
+    ReadBookmarks,
+    /// `read:favourites`
+    ReadFavourites,
+    /// `read:filters`
+    ReadFilters,
+    /// `read:follows`
+    ReadFollows,
+    /// `read:lists`
+    ReadLists,
+    /// `read:mutes`
+    ReadMutes,
+    /// `read:notifications`
+    ReadNotifications,
+    /// `read:search`
+    ReadSearch,
+    /// `read:statuses`
+    ReadStatuses,
+    /// `write`: all granular `write:*` scopes.
+    Write,
+    /// `write:accounts`
+    WriteAccounts,
+    /// `write:blocks`
+    WriteBlocks,
+    /// `write:bookmarks`
+    WriteBookmarks,
+    /// `write:conversations`
+    WriteConversations,
+    /// `write:favourites`
+    WriteFavourites,
+    /// `write:filters`
+    WriteFilters,
+    /// `write:follows`
+    WriteFollows,
+    /// `write:lists`
+    WriteLists,
+    /// `write:media`
+    WriteMedia,
+    /// `write:mutes`
+    WriteMutes,
+    /// `write:notifications`
+    WriteNotifications,
+    /// `write:reports`
+    WriteReports,
+    /// `write:statuses`
+    WriteStatuses,
+    /// `follow`: reading and changing follows, blocks and mutes (deprecated since Mastodon 3.5.0).
+    Follow,
+    /// `push`: Web Push subscriptions.
+    Push,
+    /// `profile`: only `GET /api/v1/accounts/verify_credentials` (Mastodon 4.3.0 and later).
+    Profile,
+    /// `admin:read`: all granular `admin:read:*` scopes.
+    AdminRead,
+    /// `admin:read:accounts`
+    AdminReadAccounts,
+    /// `admin:read:reports`
+    AdminReadReports,
+    /// `admin:read:domain_allows`
+    AdminReadDomainAllows,
+    /// `admin:read:domain_blocks`
+    AdminReadDomainBlocks,
+    /// `admin:read:ip_blocks`
+    AdminReadIpBlocks,
+    /// `admin:read:email_domain_blocks`
+    AdminReadEmailDomainBlocks,
+    /// `admin:read:canonical_email_blocks`
+    AdminReadCanonicalEmailBlocks,
+    /// `admin:write`: all granular `admin:write:*` scopes.
+    AdminWrite,
+    /// `admin:write:accounts`
+    AdminWriteAccounts,
+    /// `admin:write:reports`
+    AdminWriteReports,
+    /// `admin:write:domain_allows`
+    AdminWriteDomainAllows,
+    /// `admin:write:domain_blocks`
+    AdminWriteDomainBlocks,
+    /// `admin:write:ip_blocks`
+    AdminWriteIpBlocks,
+    /// `admin:write:email_domain_blocks`
+    AdminWriteEmailDomainBlocks,
+    /// `admin:write:canonical_email_blocks`
+    AdminWriteCanonicalEmailBlocks,
+}
+
+impl Scope {
+    /// All known scopes.
+    pub const ALL: &'static [Scope] = &[
+        Scope::Read,
+        Scope::ReadAccounts,
+        Scope::ReadBlocks,
+        Scope::ReadBookmarks,
+        Scope::ReadFavourites,
+        Scope::ReadFilters,
+        Scope::ReadFollows,
+        Scope::ReadLists,
+        Scope::ReadMutes,
+        Scope::ReadNotifications,
+        Scope::ReadSearch,
+        Scope::ReadStatuses,
+        Scope::Write,
+        Scope::WriteAccounts,
+        Scope::WriteBlocks,
+        Scope::WriteBookmarks,
+        Scope::WriteConversations,
+        Scope::WriteFavourites,
+        Scope::WriteFilters,
+        Scope::WriteFollows,
+        Scope::WriteLists,
+        Scope::WriteMedia,
+        Scope::WriteMutes,
+        Scope::WriteNotifications,
+        Scope::WriteReports,
+        Scope::WriteStatuses,
+        Scope::Follow,
+        Scope::Push,
+        Scope::Profile,
+        Scope::AdminRead,
+        Scope::AdminReadAccounts,
+        Scope::AdminReadReports,
+        Scope::AdminReadDomainAllows,
+        Scope::AdminReadDomainBlocks,
+        Scope::AdminReadIpBlocks,
+        Scope::AdminReadEmailDomainBlocks,
+        Scope::AdminReadCanonicalEmailBlocks,
+        Scope::AdminWrite,
+        Scope::AdminWriteAccounts,
+        Scope::AdminWriteReports,
+        Scope::AdminWriteDomainAllows,
+        Scope::AdminWriteDomainBlocks,
+        Scope::AdminWriteIpBlocks,
+        Scope::AdminWriteEmailDomainBlocks,
+        Scope::AdminWriteCanonicalEmailBlocks,
+    ];
+
+    /// Returns the scope as sent to the server, e.g. `read:statuses`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::ReadAccounts => "read:accounts",
+            Scope::ReadBlocks => "read:blocks",
+            Scope::ReadBookmarks => "read:bookmarks",
+            Scope::ReadFavourites => "read:favourites",
+            Scope::ReadFilters => "read:filters",
+            Scope::ReadFollows => "read:follows",
+            Scope::ReadLists => "read:lists",
+            Scope::ReadMutes => "read:mutes",
+            Scope::ReadNotifications => "read:notifications",
+            Scope::ReadSearch => "read:search",
+            Scope::ReadStatuses => "read:statuses",
+            Scope::Write => "write",
+            Scope::WriteAccounts => "write:accounts",
+            Scope::WriteBlocks => "write:blocks",
+            Scope::WriteBookmarks => "write:bookmarks",
+            Scope::WriteConversations => "write:conversations",
+            Scope::WriteFavourites => "write:favourites",
+            Scope::WriteFilters => "write:filters",
+            Scope::WriteFollows => "write:follows",
+            Scope::WriteLists => "write:lists",
+            Scope::WriteMedia => "write:media",
+            Scope::WriteMutes => "write:mutes",
+            Scope::WriteNotifications => "write:notifications",
+            Scope::WriteReports => "write:reports",
+            Scope::WriteStatuses => "write:statuses",
+            Scope::Follow => "follow",
+            Scope::Push => "push",
+            Scope::Profile => "profile",
+            Scope::AdminRead => "admin:read",
+            Scope::AdminReadAccounts => "admin:read:accounts",
+            Scope::AdminReadReports => "admin:read:reports",
+            Scope::AdminReadDomainAllows => "admin:read:domain_allows",
+            Scope::AdminReadDomainBlocks => "admin:read:domain_blocks",
+            Scope::AdminReadIpBlocks => "admin:read:ip_blocks",
+            Scope::AdminReadEmailDomainBlocks => "admin:read:email_domain_blocks",
+            Scope::AdminReadCanonicalEmailBlocks => "admin:read:canonical_email_blocks",
+            Scope::AdminWrite => "admin:write",
+            Scope::AdminWriteAccounts => "admin:write:accounts",
+            Scope::AdminWriteReports => "admin:write:reports",
+            Scope::AdminWriteDomainAllows => "admin:write:domain_allows",
+            Scope::AdminWriteDomainBlocks => "admin:write:domain_blocks",
+            Scope::AdminWriteIpBlocks => "admin:write:ip_blocks",
+            Scope::AdminWriteEmailDomainBlocks => "admin:write:email_domain_blocks",
+            Scope::AdminWriteCanonicalEmailBlocks => "admin:write:canonical_email_blocks",
+        }
+    }
+
+    /// Returns the top-level scope this granular scope belongs to, e.g. `read` for
+    /// `read:statuses`.
+    pub fn parent(&self) -> Option<Scope> {
+        let name = self.as_str();
+        let parent = name.rsplit_once(':')?.0;
+        Scope::ALL
+            .iter()
+            .copied()
+            .find(|scope| scope.as_str() == parent)
+    }
+
+    /// Returns whether a token granted this scope may use endpoints requiring `other`.
+    pub fn covers(&self, other: Scope) -> bool {
+        *self == other
+            || other.parent() == Some(*self)
+            || (*self == Scope::Follow
+                && matches!(
+                    other,
+                    Scope::ReadBlocks
+                        | Scope::WriteBlocks
+                        | Scope::ReadFollows
+                        | Scope::WriteFollows
+                        | Scope::ReadMutes
+                        | Scope::WriteMutes
+                ))
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = MastodonError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        Scope::ALL
+            .iter()
+            .copied()
+            .find(|scope| scope.as_str() == value)
+            .ok_or_else(|| MastodonError::Custom(format!("Unknown OAuth scope: {}", value)))
+    }
+}
+
+/// A set of OAuth scopes.
+///
+/// Formats as the space-separated list expected by `RegisterAppParams::scopes` and
+/// [`OAuth::scopes`](crate::oauth::OAuth::scopes).
+///
+/// # Example
+/// ```
+/// use mastodon_api::scopes::{Scope, Scopes};
+///
+/// let scopes: Scopes = "read write:media write:statuses".parse().unwrap();
+/// assert!(scopes.allows(Scope::ReadNotifications));
+/// assert!(scopes.allows(Scope::WriteMedia));
+/// assert!(!scopes.allows(Scope::WriteFollows));
+/// assert_eq!(scopes.to_string(), "read write:media write:statuses");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scopes {
+    scopes: BTreeSet<Scope>,
+}
+
+impl Scopes {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a scope.
+    pub fn with(mut self, scope: Scope) -> Self {
+        self.insert(scope);
+        self
+    }
+
+    /// Adds a scope.
+    pub fn insert(&mut self, scope: Scope) {
+        self.scopes.insert(scope);
+    }
+
+    /// Returns whether the scope is in the set itself.
+    pub fn contains(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    /// Returns whether the set grants `scope`, directly or through a broader scope.
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.scopes.iter().any(|granted| granted.covers(scope))
+    }
+
+    /// Returns the scopes in the set.
+    pub fn iter(&self) -> impl Iterator<Item = Scope> + '_ {
+        self.scopes.iter().copied()
+    }
+
+    /// Returns the number of scopes in the set.
+    pub fn len(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Returns `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty()
+    }
+
+    /// Parses a space-separated list, skipping scopes this crate does not know.
+    ///
+    /// Used for scopes reported by the server, which may be newer than the crate.
+    pub fn parse_lenient(value: &str) -> Self {
+        value
+            .split_whitespace()
+            .filter_map(|scope| scope.parse().ok())
+            .collect()
+    }
+}
+
+impl fmt::Display for Scopes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, scope) in self.scopes.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_str(scope.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Scopes {
+    type Err = MastodonError;
+
+    /// Parses a space-separated list, failing on unknown scopes.
+    fn from_str(value: &str) -> Result<Self> {
+        value.split_whitespace().map(str::parse).collect()
+    }
+}
+
+impl FromIterator<Scope> for Scopes {
+    fn from_iter<I: IntoIterator<Item = Scope>>(iter: I) -> Self {
+        Self {
+            scopes: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl From<Scope> for Scopes {
+    fn from(scope: Scope) -> Self {
+        Self::new().with(scope)
+    }
+}
+
+/// Returns the scopes of which one is needed for the request, or none if the endpoint
+/// is public or not covered by the table.
+///
+/// Requests are rejected based on this table before they are sent, so it only lists
+/// endpoints whose scopes are documented; anything else is left to the server.
+pub(crate) fn required(method: &Method, path: &str) -> &'static [Scope] {
+    use Scope::*;
+
+    let read = *method == Method::GET;
+    let either = |read_scope: &'static [Scope], write_scope: &'static [Scope]| {
+        if read { read_scope } else { write_scope }
+    };
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let ["api", _, rest @ ..] = segments.as_slice() else {
+        return &[];
+    };
+
+    match rest {
+        ["accounts", "verify_credentials"] => &[ReadAccounts, Profile],
+        ["accounts", "update_credentials"] => &[WriteAccounts],
+        ["accounts", "relationships" | "familiar_followers"] => &[ReadFollows],
+        ["accounts"] if !read => &[WriteAccounts],
+        ["accounts", _, "statuses"] => &[ReadStatuses],
+        ["accounts", _, "lists"] => &[ReadLists],
+        [
+            "accounts",
+            _,
+            "follow" | "unfollow" | "remove_from_followers",
+        ] => &[WriteFollows],
+        ["accounts", _, "block" | "unblock"] => &[WriteBlocks],
+        ["accounts", _, "mute" | "unmute"] => &[WriteMutes],
+        ["accounts", _, "pin" | "unpin" | "note"] => &[WriteAccounts],
+        ["accounts", ..] if read => &[ReadAccounts],
+        ["statuses", _, "translate"] => &[ReadStatuses],
+        ["statuses", _, "favourited_by" | "reblogged_by"] => &[ReadAccounts],
+        ["statuses", _, "favourite" | "unfavourite"] => &[WriteFavourites],
+        ["statuses", _, "bookmark" | "unbookmark"] => &[WriteBookmarks],
+        ["statuses", _, "mute" | "unmute"] => &[WriteMutes],
+        ["statuses", _, "pin" | "unpin"] => &[WriteAccounts],
+        ["timelines", "list", _] => &[ReadLists],
+        ["statuses", ..] | ["timelines", ..] | ["markers"] => {
+            either(&[ReadStatuses], &[WriteStatuses])
+        }
+        ["conversations", ..] => either(&[ReadStatuses], &[WriteConversations]),
+        ["favourites"] => &[ReadFavourites],
+        ["bookmarks"] => &[ReadBookmarks],
+        ["blocks"] => &[ReadBlocks],
+        ["mutes"] => &[ReadMutes],
+        ["domain_blocks"] => either(&[ReadBlocks], &[WriteBlocks]),
+        ["follow_requests", ..] => either(&[ReadFollows], &[WriteFollows]),
+        ["followed_tags"] => &[ReadFollows],
+        ["tags", _, "follow" | "unfollow"] => &[WriteFollows],
+        ["lists", ..] => either(&[ReadLists], &[WriteLists]),
+        ["filters", ..] => either(&[ReadFilters], &[WriteFilters]),
+        ["notifications", ..] => either(&[ReadNotifications], &[WriteNotifications]),
+        ["media", ..] => &[WriteMedia],
+        ["reports"] if !read => &[WriteReports],
+        ["search"] => &[ReadSearch],
+        ["preferences"] | ["endorsements"] => &[ReadAccounts],
+        ["featured_tags", ..] => either(&[ReadAccounts], &[WriteAccounts]),
+        ["suggestions", ..] if read => &[ReadAccounts],
+        ["push", ..] => &[Push],
+        ["admin", kind, ..] => match (*kind, read) {
+            ("accounts", true) => &[AdminReadAccounts],
+            ("accounts", false) => &[AdminWriteAccounts],
+            ("reports", true) => &[AdminReadReports],
+            ("reports", false) => &[AdminWriteReports],
+            ("domain_allows", true) => &[AdminReadDomainAllows],
+            ("domain_allows", false) => &[AdminWriteDomainAllows],
+            ("domain_blocks", true) => &[AdminReadDomainBlocks],
+            ("domain_blocks", false) => &[AdminWriteDomainBlocks],
+            ("ip_blocks", true) => &[AdminReadIpBlocks],
+            ("ip_blocks", false) => &[AdminWriteIpBlocks],
+            ("email_domain_blocks", true) => &[AdminReadEmailDomainBlocks],
+            ("email_domain_blocks", false) => &[AdminWriteEmailDomainBlocks],
+            ("canonical_email_blocks", true) => &[AdminReadCanonicalEmailBlocks],
+            ("canonical_email_blocks", false) => &[AdminWriteCanonicalEmailBlocks],
+            _ => &[],
+        },
+        _ => &[],
+    }
+}
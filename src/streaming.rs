@@ -1,8 +1,22 @@
+use crate::builder::ConnectionConfig;
 use crate::error::{MastodonError, Result};
+use crate::middleware::Middleware;
 use crate::models::{Notification, Status};
+use crate::response::HttpResponse;
+use crate::trace;
+use base64::Engine;
 use futures_util::StreamExt;
+use reqwest::Method;
+use reqwest::header::{AUTHORIZATION, HeaderValue, USER_AGENT};
 use serde::Deserialize;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_socks::TargetAddr;
+use tokio_socks::tcp::Socks5Stream;
+use tokio_tungstenite::client_async_tls;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::protocol::Message;
 use url::Url;
 
 /// Raw event received from the Mastodon streaming API.
@@ -23,6 +37,18 @@ pub enum StreamEvent {
     FiltersChanged,
 }
 
+impl StreamEvent {
+    /// Returns the name of the event, as sent by the server.
+    fn name(&self) -> &'static str {
+        match self {
+            StreamEvent::Update(_) => "update",
+            StreamEvent::Notification(_) => "notification",
+            StreamEvent::Delete(_) => "delete",
+            StreamEvent::FiltersChanged => "filters_changed",
+        }
+    }
+}
+
 /// A high-level, parsed event from the Mastodon stream.
 #[derive(Debug)]
 pub enum MastodonEvent {
@@ -40,8 +66,17 @@ pub enum MastodonEvent {
 pub struct StreamingClient {
     stream_url: String,
     access_token: Option<String>,
+    connection: ConnectionConfig,
+    middleware: Vec<Arc<dyn Middleware>>,
 }
 
+/// Byte stream underlying a WebSocket connection, possibly tunneled through a proxy.
+trait Io: AsyncRead + AsyncWrite + Send + Unpin {}
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Io for T {}
+
+type EventStream =
+    std::pin::Pin<Box<dyn futures_util::Stream<Item = Result<MastodonEvent>> + Send>>;
+
 impl StreamingClient {
     pub fn new(base_url: &str, access_token: Option<String>) -> Self {
         let stream_url = base_url
@@ -51,9 +86,23 @@ impl StreamingClient {
         Self {
             stream_url,
             access_token,
+            connection: ConnectionConfig::default(),
+            middleware: Vec::new(),
         }
     }
 
+    /// Applies the user agent, default headers, connect timeout and proxy of a client.
+    pub(crate) fn with_connection(mut self, connection: ConnectionConfig) -> Self {
+        self.connection = connection;
+        self
+    }
+
+    /// Applies the middleware of a client to the connection handshake.
+    pub(crate) fn with_middleware(mut self, middleware: Vec<Arc<dyn Middleware>>) -> Self {
+        self.middleware = middleware;
+        self
+    }
+
     /// Subscribes to a specific stream type.
     ///
     /// # Common Stream Types
@@ -63,45 +112,214 @@ impl StreamingClient {
     /// - `hashtag`: Statuses containing a specific tag.
     ///
     /// Returns a pinned stream of `MastodonEvent`s.
-    pub async fn subscribe(
-        &self,
-        stream_type: &str,
-    ) -> Result<std::pin::Pin<Box<dyn futures_util::Stream<Item = Result<MastodonEvent>> + Send>>>
-    {
+    pub async fn subscribe(&self, stream_type: &str) -> Result<EventStream> {
+        let span = trace::stream_span(stream_type);
+        let result = trace::instrument(&span, self.connect(stream_type, &span)).await;
+        if let Err(err) = &result {
+            trace::failure(&span, err);
+            for middleware in &self.middleware {
+                middleware.on_error(err).await;
+            }
+        }
+        result
+    }
+
+    async fn connect(&self, stream_type: &str, span: &trace::Span) -> Result<EventStream> {
         let mut url = Url::parse(&self.stream_url)?;
         url.query_pairs_mut().append_pair("stream", stream_type);
+
+        // The handshake is passed through the middleware as a regular GET request. The
+        // token goes into a header rather than the query, so it never shows up in URLs.
+        let mut handshake = reqwest::Request::new(Method::GET, url);
+        let headers = handshake.headers_mut();
         if let Some(token) = &self.access_token {
-            url.query_pairs_mut().append_pair("access_token", token);
+            let value = HeaderValue::from_str(&format!("Bearer {}", token))
+                .map_err(|e| MastodonError::Custom(e.to_string()))?;
+            headers.insert(AUTHORIZATION, value);
+        }
+        for (name, value) in &self.connection.default_headers {
+            headers.insert(name, value.clone());
+        }
+        if let Some(user_agent) = &self.connection.user_agent {
+            let value = HeaderValue::from_str(user_agent)
+                .map_err(|e| MastodonError::Custom(e.to_string()))?;
+            headers.insert(USER_AGENT, value);
+        }
+        for middleware in &self.middleware {
+            middleware.before_request(&mut handshake).await?;
         }
 
-        let (ws_stream, _) = connect_async(url.to_string())
-            .await
+        let url = handshake.url().clone();
+        let mut request = url
+            .as_str()
+            .into_client_request()
             .map_err(|e| MastodonError::Custom(e.to_string()))?;
+        request.headers_mut().extend(handshake.headers().clone());
+
+        let connect = async {
+            let stream = self.open(&url).await?;
+            client_async_tls(request, stream)
+                .await
+                .map_err(|e| MastodonError::Custom(e.to_string()))
+        };
+        let (ws_stream, response) = match self.connection.connect_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, connect)
+                .await
+                .map_err(|_| MastodonError::Custom("Streaming connection timed out".into()))??,
+            None => connect.await?,
+        };
+
+        let response = HttpResponse {
+            method: Method::GET,
+            url,
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response.body().clone().unwrap_or_default(),
+        };
+        for middleware in &self.middleware {
+            middleware.after_response(&response).await?;
+        }
+
+        trace::stream_event(span, "connected");
+
         let (_, read) = ws_stream.split();
+        let span = span.clone();
 
-        Ok(Box::pin(read.filter_map(|msg| async {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    let event: StreamEvent = match serde_json::from_str(&text) {
-                        Ok(e) => e,
-                        Err(_) => return None,
-                    };
-
-                    match event {
-                        StreamEvent::Update(payload) => {
-                            let status: Status = serde_json::from_str(&payload).ok()?;
-                            Some(Ok(MastodonEvent::Update(Box::new(status))))
-                        }
-                        StreamEvent::Notification(payload) => {
-                            let notification: Notification = serde_json::from_str(&payload).ok()?;
-                            Some(Ok(MastodonEvent::Notification(Box::new(notification))))
+        Ok(Box::pin(read.filter_map(move |msg| {
+            let span = span.clone();
+            async move {
+                match msg {
+                    Ok(Message::Text(text)) => {
+                        let event: StreamEvent = match serde_json::from_str(&text) {
+                            Ok(e) => e,
+                            Err(_) => return None,
+                        };
+
+                        trace::stream_event(&span, event.name());
+                        match event {
+                            StreamEvent::Update(payload) => {
+                                let status: Status = serde_json::from_str(&payload).ok()?;
+                                Some(Ok(MastodonEvent::Update(Box::new(status))))
+                            }
+                            StreamEvent::Notification(payload) => {
+                                let notification: Notification =
+                                    serde_json::from_str(&payload).ok()?;
+                                Some(Ok(MastodonEvent::Notification(Box::new(notification))))
+                            }
+                            StreamEvent::Delete(id) => Some(Ok(MastodonEvent::Delete(id))),
+                            StreamEvent::FiltersChanged => Some(Ok(MastodonEvent::FiltersChanged)),
                         }
-                        StreamEvent::Delete(id) => Some(Ok(MastodonEvent::Delete(id))),
-                        StreamEvent::FiltersChanged => Some(Ok(MastodonEvent::FiltersChanged)),
                     }
+                    Ok(Message::Close(_)) => {
+                        trace::stream_event(&span, "closed");
+                        None
+                    }
+                    Err(err) => {
+                        trace::failure(&span, &MastodonError::Custom(err.to_string()));
+                        None
+                    }
+                    _ => None,
                 }
-                _ => None,
             }
         })))
     }
+
+    /// Opens the TCP connection to the streaming server, tunneling through the proxy if configured.
+    async fn open(&self, url: &Url) -> Result<Box<dyn Io>> {
+        let host = url
+            .host_str()
+            .ok_or_else(|| MastodonError::Custom("Streaming URL has no host".into()))?;
+        let port = url.port_or_known_default().unwrap_or(443);
+        let io_err = |e: std::io::Error| MastodonError::Custom(e.to_string());
+
+        let Some(proxy) = &self.connection.proxy else {
+            let stream = TcpStream::connect((host, port)).await.map_err(io_err)?;
+            return Ok(Box::new(stream));
+        };
+
+        let proxy_host = proxy
+            .host_str()
+            .ok_or_else(|| MastodonError::Custom("Proxy URL has no host".into()))?;
+        let proxy_port = proxy.port_or_known_default().unwrap_or(1080);
+        let username = percent_decode(proxy.username());
+        let password = proxy.password().map(percent_decode);
+
+        match proxy.scheme() {
+            "socks5" | "socks5h" => {
+                let proxy_addr = (proxy_host, proxy_port);
+                // `socks5h` leaves name resolution to the proxy, `socks5` resolves locally.
+                let target = if proxy.scheme() == "socks5" {
+                    let addr = tokio::net::lookup_host((host, port))
+                        .await
+                        .map_err(io_err)?
+                        .next()
+                        .ok_or_else(|| {
+                            MastodonError::Custom(format!("Could not resolve {}", host))
+                        })?;
+                    TargetAddr::Ip(addr)
+                } else {
+                    TargetAddr::Domain(host.into(), port)
+                };
+                let stream = match &password {
+                    Some(password) => {
+                        Socks5Stream::connect_with_password(proxy_addr, target, &username, password)
+                            .await
+                    }
+                    None => Socks5Stream::connect(proxy_addr, target).await,
+                }
+                .map_err(|e| MastodonError::Custom(e.to_string()))?;
+                Ok(Box::new(stream))
+            }
+            _ => {
+                let mut stream = TcpStream::connect((proxy_host, proxy_port))
+                    .await
+                    .map_err(io_err)?;
+                let mut connect =
+                    format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+                if !username.is_empty() {
+                    let credentials = format!("{}:{}", username, password.unwrap_or_default());
+                    connect += &format!(
+                        "Proxy-Authorization: Basic {}\r\n",
+                        base64::engine::general_purpose::STANDARD.encode(credentials)
+                    );
+                }
+                connect += "\r\n";
+                stream.write_all(connect.as_bytes()).await.map_err(io_err)?;
+
+                // Read the proxy's response headers up to the blank line.
+                let mut response = Vec::new();
+                let mut byte = [0u8; 1];
+                while !response.ends_with(b"\r\n\r\n") {
+                    if response.len() > 8192 {
+                        return Err(MastodonError::Custom(
+                            "Proxy sent an oversized response to CONNECT".into(),
+                        ));
+                    }
+                    if stream.read(&mut byte).await.map_err(io_err)? == 0 {
+                        return Err(MastodonError::Custom(
+                            "Proxy closed the connection during CONNECT".into(),
+                        ));
+                    }
+                    response.push(byte[0]);
+                }
+                let status_line = String::from_utf8_lossy(&response);
+                let status_line = status_line.lines().next().unwrap_or_default();
+                if status_line.split_whitespace().nth(1) != Some("200") {
+                    return Err(MastodonError::Custom(format!(
+                        "Proxy refused CONNECT: {}",
+                        status_line
+                    )));
+                }
+                Ok(Box::new(stream))
+            }
+        }
+    }
+}
+
+/// Decodes percent-encoded proxy credentials.
+fn percent_decode(value: &str) -> String {
+    url::form_urlencoded::parse(format!("v={}", value.replace('+', "%2B")).as_bytes())
+        .next()
+        .map(|(_, v)| v.into_owned())
+        .unwrap_or_default()
 }
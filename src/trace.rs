@@ -0,0 +1,125 @@
+//! Instrumentation with the `tracing` crate, enabled by the `tracing` feature.
+//!
+//! Without the feature every function here is a no-op, so call sites don't need
+//! their own `cfg` attributes. Only methods, path templates and statuses are
+//! recorded; URLs with their query strings (which may carry the streaming access
+//! token) and request headers never are.
+
+use reqwest::{Method, StatusCode};
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::Span;
+
+/// Stand-in for `tracing::Span` when the feature is disabled.
+#[cfg(not(feature = "tracing"))]
+#[derive(Debug, Clone)]
+pub(crate) struct Span;
+
+/// Creates the span covering a request, including all of its attempts.
+#[allow(unused_variables)]
+pub(crate) fn request_span(method: &Method, path: &str) -> Span {
+    #[cfg(feature = "tracing")]
+    return tracing::info_span!(
+        "mastodon.request",
+        method = %method,
+        path = %path_template(path),
+        status = tracing::field::Empty,
+        attempts = tracing::field::Empty,
+        latency_ms = tracing::field::Empty,
+        rate_limit_remaining = tracing::field::Empty,
+    );
+    #[cfg(not(feature = "tracing"))]
+    Span
+}
+
+/// Creates the span covering a streaming connection.
+#[allow(unused_variables)]
+pub(crate) fn stream_span(stream_type: &str) -> Span {
+    #[cfg(feature = "tracing")]
+    return tracing::info_span!("mastodon.stream", stream = %stream_type);
+    #[cfg(not(feature = "tracing"))]
+    Span
+}
+
+/// Runs the future within the span.
+pub(crate) async fn instrument<F: Future>(span: &Span, future: F) -> F::Output {
+    #[cfg(feature = "tracing")]
+    return tracing::Instrument::instrument(future, span.clone()).await;
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = span;
+        future.await
+    }
+}
+
+/// Records a response received for an attempt of the request.
+#[allow(unused_variables)]
+pub(crate) fn response(
+    span: &Span,
+    attempt: u32,
+    status: StatusCode,
+    latency: Duration,
+    rate_limit_remaining: Option<u32>,
+) {
+    #[cfg(feature = "tracing")]
+    {
+        let latency_ms = latency.as_millis() as u64;
+        span.record("status", status.as_u16());
+        span.record("attempts", attempt);
+        span.record("latency_ms", latency_ms);
+        if let Some(remaining) = rate_limit_remaining {
+            span.record("rate_limit_remaining", remaining);
+        }
+        tracing::debug!(
+            parent: span,
+            attempt,
+            status = status.as_u16(),
+            latency_ms,
+            "received response"
+        );
+    }
+}
+
+/// Records that an attempt is retried after the given delay.
+#[allow(unused_variables)]
+pub(crate) fn retry(span: &Span, attempt: u32, delay: Duration, reason: &dyn std::fmt::Display) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        parent: span,
+        attempt,
+        delay_ms = delay.as_millis() as u64,
+        reason = %reason,
+        "retrying request"
+    );
+}
+
+/// Records that the request or connection failed.
+#[allow(unused_variables)]
+pub(crate) fn failure(span: &Span, error: &crate::error::MastodonError) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(parent: span, error = %error, "request failed");
+}
+
+/// Records an event of the streaming connection, such as `connected` or `update`.
+#[allow(unused_variables)]
+pub(crate) fn stream_event(span: &Span, event: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(parent: span, event, "stream event");
+}
+
+/// Replaces the IDs in a path with `:id`, so requests to the same endpoint share a name.
+#[cfg(feature = "tracing")]
+fn path_template(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+                ":id"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
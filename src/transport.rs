@@ -0,0 +1,217 @@
+//! The layer that actually sends requests over the network.
+//!
+//! [`MastodonClient`](crate::MastodonClient) hands every request to a [`Transport`].
+//! By default this is [`ReqwestTransport`]; tests can swap in a [`MockTransport`]
+//! scripted with canned responses, so bot logic runs without opening sockets.
+
+use crate::error::{MastodonError, Result};
+use crate::response::HttpResponse;
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, Method, StatusCode};
+use serde::Serialize;
+use std::sync::Mutex;
+use url::Url;
+
+/// Sends a request and returns the fully received response.
+///
+/// Implementations must not treat error statuses as failures; status handling,
+/// retries and error parsing are done by the client.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Sends the request and returns its status, headers and body.
+    async fn send(&self, request: reqwest::Request) -> Result<HttpResponse>;
+}
+
+/// The default transport, sending requests with a `reqwest::Client`.
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    /// Creates a new `ReqwestTransport` using the given client.
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn send(&self, request: reqwest::Request) -> Result<HttpResponse> {
+        let method = request.method().clone();
+        let response = self.client.execute(request).await?;
+        Ok(HttpResponse {
+            method,
+            url: response.url().clone(),
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response.bytes().await?.to_vec(),
+        })
+    }
+}
+
+/// A canned response served by a [`MockTransport`].
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl MockResponse {
+    /// Creates an empty response with the given status.
+    pub fn new(status: u16) -> Self {
+        Self {
+            status: StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Creates a `200 OK` response with the given value serialized as JSON.
+    pub fn json(value: &impl Serialize) -> Self {
+        Self::new(200).with_json(value)
+    }
+
+    /// Sets the body to the given value serialized as JSON.
+    pub fn with_json(self, value: &impl Serialize) -> Self {
+        let body = serde_json::to_vec(value).unwrap_or_default();
+        self.with_header("content-type", "application/json")
+            .with_body(body)
+    }
+
+    /// Sets the raw body.
+    pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Adds a response header.
+    ///
+    /// # Panics
+    /// Panics if the name or value are not valid header contents.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(
+            HeaderName::from_bytes(name.as_bytes()).expect("invalid header name"),
+            HeaderValue::from_str(value).expect("invalid header value"),
+        );
+        self
+    }
+}
+
+/// A request received by a [`MockTransport`].
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// Method of the request.
+    pub method: Method,
+    /// Full URL of the request, including the query string.
+    pub url: Url,
+    /// Headers of the request.
+    pub headers: HeaderMap,
+    /// Body of the request; empty for streaming bodies such as file uploads.
+    pub body: Vec<u8>,
+}
+
+/// An in-memory transport serving scripted responses.
+///
+/// Responses are queued per method and path and served in order, each one once.
+/// Requests without a queued response fail with [`MastodonError::Custom`].
+///
+/// # Example
+/// ```
+/// use mastodon_api::MastodonClient;
+/// use mastodon_api::transport::{MockResponse, MockTransport};
+/// use reqwest::Method;
+/// use std::sync::Arc;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let transport = Arc::new(MockTransport::new());
+/// transport.push(Method::GET, "/api/v1/domain_blocks", MockResponse::json(&["spam.example"]));
+///
+/// let client = MastodonClient::new("https://mastodon.social").with_transport(transport.clone());
+/// let blocked = client.domain_blocks().list().await.unwrap();
+///
+/// assert_eq!(blocked, vec!["spam.example".to_string()]);
+/// assert_eq!(transport.requests()[0].url.path(), "/api/v1/domain_blocks");
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    responses: Mutex<Vec<(Method, String, MockResponse)>>,
+    requests: Mutex<Vec<RecordedRequest>>,
+}
+
+impl MockTransport {
+    /// Creates a new `MockTransport` without any responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a response for the next request with the given method and path.
+    pub fn push(&self, method: Method, path: &str, response: MockResponse) {
+        self.responses
+            .lock()
+            .unwrap()
+            .push((method, path.to_string(), response));
+    }
+
+    /// Returns all requests received so far.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Returns the number of queued responses that have not been served yet.
+    pub fn pending(&self) -> usize {
+        self.responses.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl Transport for MockTransport {
+    async fn send(&self, request: reqwest::Request) -> Result<HttpResponse> {
+        let recorded = RecordedRequest {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            headers: request.headers().clone(),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(<[u8]>::to_vec)
+                .unwrap_or_default(),
+        };
+        self.requests.lock().unwrap().push(recorded);
+
+        let response = {
+            let mut responses = self.responses.lock().unwrap();
+            let position = responses.iter().position(|(method, path, _)| {
+                method == request.method() && path == request.url().path()
+            });
+            position.map(|index| responses.remove(index).2)
+        };
+
+        match response {
+            Some(response) => Ok(HttpResponse {
+                method: request.method().clone(),
+                url: request.url().clone(),
+                status: response.status,
+                headers: response.headers,
+                body: response.body,
+            }),
+            None => Err(MastodonError::Custom(format!(
+                "No mock response for {} {}",
+                request.method(),
+                request.url().path()
+            ))),
+        }
+    }
+}
+
+/// Lets a transport be shared, e.g. to inspect a [`MockTransport`] after handing it to a client.
+#[async_trait]
+impl<T: Transport + ?Sized> Transport for std::sync::Arc<T> {
+    async fn send(&self, request: reqwest::Request) -> Result<HttpResponse> {
+        (**self).send(request).await
+    }
+}
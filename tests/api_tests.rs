@@ -1,6 +1,27 @@
-use mastodon_api::MastodonClient;
-use mockito::Server;
+use async_trait::async_trait;
+use mastodon_api::capabilities::{Feature, ServerVersion};
+use mastodon_api::cassette::CassetteTransport;
+use mastodon_api::credentials::{CredentialStore, JsonFileStore};
+use mastodon_api::methods::accounts::RegisterAccountParams;
+use mastodon_api::methods::apps::RegisterAppParams;
+use mastodon_api::methods::statuses::CreateStatusParams;
+use mastodon_api::middleware::Middleware;
+use mastodon_api::oauth::{LoopbackLogin, OAuth};
+use mastodon_api::outbox::Outbox;
+use mastodon_api::pool::AccountPool;
+use mastodon_api::response::HttpResponse;
+use mastodon_api::scopes::{Scope, Scopes};
+use mastodon_api::transport::{MockResponse, MockTransport, Transport};
+use mastodon_api::{
+    ConcurrencyLimiter, MastodonClient, MastodonError, RateLimiter, ResponseCache, RetryPolicy,
+};
+use mockito::{Matcher, Server};
+use reqwest::Method;
+use reqwest::header::{HeaderName, HeaderValue};
 use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 #[tokio::test]
 async fn test_get_instance() {
@@ -67,3 +88,1646 @@ async fn test_rate_limiting_retry() {
 
     assert_eq!(instance.uri, "mastodon.social");
 }
+
+#[tokio::test]
+async fn test_rate_limit_governor() {
+    let mut server = Server::new_async().await;
+    let url = server.url();
+
+    let m = server
+        .mock("GET", "/api/v1/instance")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("X-RateLimit-Limit", "300")
+        .with_header("X-RateLimit-Remaining", "0")
+        .with_header("X-RateLimit-Reset", "2099-01-01T00:00:00.000Z")
+        .with_body(
+            json!({
+                "uri": "mastodon.social",
+                "title": "Mastodon",
+                "description": "The original Mastodon instance",
+                "email": "admin@mastodon.social",
+                "version": "4.2.0"
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let limiter = RateLimiter::new().max_wait(Duration::from_secs(1));
+    let client = MastodonClient::new(&url).with_rate_limiter(limiter);
+    client.instance().get().await.unwrap();
+
+    let rate_limit = client.rate_limit().unwrap();
+    assert_eq!(rate_limit.limit, 300);
+    assert_eq!(rate_limit.remaining, 0);
+
+    // The budget is spent until 2099, so the request is held back without being sent,
+    // including for clones sharing the same limiter.
+    let err = client.clone().instance().get().await.unwrap_err();
+    assert!(matches!(err, MastodonError::RateLimit(_)));
+    m.assert_async().await;
+}
+
+fn account_json() -> serde_json::Value {
+    json!({
+        "id": "1",
+        "username": "bot",
+        "display_name": "Bot",
+        "acct": "bot",
+        "url": "https://mastodon.social/@bot",
+        "followers_count": 0,
+        "following_count": 0,
+        "statuses_count": 1,
+        "note": "",
+        "avatar": "https://mastodon.social/avatar.png",
+        "header": "https://mastodon.social/header.png",
+        "locked": false,
+        "bot": true,
+        "created_at": "2026-01-01T00:00:00.000Z"
+    })
+}
+
+fn status_json() -> serde_json::Value {
+    json!({
+        "id": "100",
+        "created_at": "2026-01-30T12:00:00.000Z",
+        "in_reply_to_id": null,
+        "in_reply_to_account_id": null,
+        "sensitive": false,
+        "spoiler_text": "",
+        "visibility": "public",
+        "language": "en",
+        "uri": "https://mastodon.social/users/bot/statuses/100",
+        "url": "https://mastodon.social/@bot/100",
+        "replies_count": 0,
+        "reblogs_count": 0,
+        "favourites_count": 0,
+        "content": "<p>Hello</p>",
+        "account": account_json()
+    })
+}
+
+#[tokio::test]
+async fn test_status_create_retried_with_idempotency_key() {
+    let mut server = Server::new_async().await;
+    let url = server.url();
+
+    let failed = server
+        .mock("POST", "/api/v1/statuses")
+        .match_header(
+            "Idempotency-Key",
+            Matcher::Regex("^[0-9a-f]{32}$".to_string()),
+        )
+        .with_status(503)
+        .expect(1)
+        .create_async()
+        .await;
+    let succeeded = server
+        .mock("POST", "/api/v1/statuses")
+        .match_header(
+            "Idempotency-Key",
+            Matcher::Regex("^[0-9a-f]{32}$".to_string()),
+        )
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(status_json().to_string())
+        .expect(1)
+        .create_async()
+        .await;
+
+    let policy = RetryPolicy::default().base_delay(Duration::from_millis(10));
+    let client = MastodonClient::new(&url).with_retry_policy(policy);
+    let status = client.statuses().create_simple("Hello").await.unwrap();
+
+    assert_eq!(status.id, "100");
+    failed.assert_async().await;
+    succeeded.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_post_without_idempotency_key_not_retried() {
+    let mut server = Server::new_async().await;
+    let url = server.url();
+
+    let m = server
+        .mock("POST", "/api/v1/accounts/1/follow")
+        .with_status(503)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let policy = RetryPolicy::default().base_delay(Duration::from_millis(10));
+    let client = MastodonClient::new(&url).with_retry_policy(policy);
+    let err = client.accounts().follow("1").await.unwrap_err();
+
+    assert!(matches!(err, MastodonError::ServiceUnavailable { .. }));
+    assert_eq!(err.status(), Some(reqwest::StatusCode::SERVICE_UNAVAILABLE));
+    m.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_retry_after_beyond_max_delay_not_retried() {
+    let mut server = Server::new_async().await;
+    let url = server.url();
+
+    let m = server
+        .mock("GET", "/api/v1/instance")
+        .with_status(503)
+        .with_header("Retry-After", "86400")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let policy = RetryPolicy::default().max_delay(Duration::from_secs(30));
+    let client = MastodonClient::new(&url).with_retry_policy(policy);
+    let err = tokio::time::timeout(Duration::from_secs(5), client.instance().get())
+        .await
+        .expect("should give up instead of waiting")
+        .unwrap_err();
+
+    assert!(matches!(err, MastodonError::ServiceUnavailable { .. }));
+    m.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_builder_user_agent_and_default_headers() {
+    let mut server = Server::new_async().await;
+    let url = server.url();
+
+    let m = server
+        .mock("GET", "/api/v1/instance")
+        .match_header("user-agent", "my-bot/1.0")
+        .match_header("x-bot-owner", "ops@example.com")
+        .match_header("authorization", "Bearer secret")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "uri": "mastodon.social",
+                "title": "Mastodon",
+                "description": "The original Mastodon instance",
+                "email": "admin@mastodon.social",
+                "version": "4.2.0"
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+
+    let client = MastodonClient::builder(&url)
+        .token("secret")
+        .user_agent("my-bot/1.0")
+        .default_header(
+            HeaderName::from_static("x-bot-owner"),
+            HeaderValue::from_static("ops@example.com"),
+        )
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap();
+    client.instance().get().await.unwrap();
+
+    m.assert_async().await;
+}
+
+#[test]
+fn test_builder_rejects_unsupported_proxy() {
+    let result = MastodonClient::builder("https://mastodon.social")
+        .proxy("ftp://127.0.0.1:21")
+        .build();
+    assert!(result.is_err());
+
+    // Streaming connections cannot open TLS to the proxy itself.
+    let result = MastodonClient::builder("https://mastodon.social")
+        .proxy("https://127.0.0.1:8443")
+        .build();
+    assert!(result.is_err());
+}
+
+#[derive(Default)]
+struct CountingMiddleware {
+    responses: AtomicUsize,
+    errors: AtomicUsize,
+}
+
+#[async_trait]
+impl Middleware for CountingMiddleware {
+    async fn before_request(&self, request: &mut reqwest::Request) -> mastodon_api::Result<()> {
+        request
+            .headers_mut()
+            .insert("x-trace-id", HeaderValue::from_static("abc123"));
+        Ok(())
+    }
+
+    async fn after_response(&self, _response: &HttpResponse) -> mastodon_api::Result<()> {
+        self.responses.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn on_error(&self, _error: &MastodonError) {
+        self.errors.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn test_middleware_hooks() {
+    let mut server = Server::new_async().await;
+    let url = server.url();
+
+    let _page = server
+        .mock("GET", "/api/v1/follow_requests")
+        .match_header("x-trace-id", "abc123")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!([account_json()]).to_string())
+        .create_async()
+        .await;
+    let _missing = server
+        .mock("GET", "/api/v1/statuses/404")
+        .match_header("x-trace-id", "abc123")
+        .with_status(404)
+        .with_body(json!({ "error": "Record not found" }).to_string())
+        .create_async()
+        .await;
+
+    let counter = Arc::new(CountingMiddleware::default());
+    let client = MastodonClient::new(&url).with_middleware(counter.clone());
+
+    let mut pages = client.follow_requests().list_paged();
+    let page = pages.next_page().await.unwrap().unwrap();
+    assert_eq!(page.len(), 1);
+    assert!(client.statuses().get("404").await.is_err());
+
+    assert_eq!(counter.responses.load(Ordering::SeqCst), 2);
+    assert_eq!(counter.errors.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_mock_transport() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::POST,
+        "/api/v1/statuses",
+        MockResponse::json(&status_json()),
+    );
+
+    let client = MastodonClient::new("https://mastodon.example")
+        .with_token("secret")
+        .with_transport(transport.clone());
+
+    let status = client.statuses().create_simple("Hello").await.unwrap();
+    assert_eq!(status.id, "100");
+
+    // Nothing is queued for this endpoint, so the request fails without touching the network.
+    assert!(client.instance().get().await.is_err());
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(
+        requests[0].url.as_str(),
+        "https://mastodon.example/api/v1/statuses"
+    );
+    assert_eq!(requests[0].headers["authorization"], "Bearer secret");
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["status"], "Hello");
+    assert_eq!(transport.pending(), 0);
+}
+
+#[tokio::test]
+async fn test_typed_api_errors() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::GET,
+        "/api/v1/statuses/1",
+        MockResponse::new(404).with_json(&json!({ "error": "Record not found" })),
+    );
+    transport.push(
+        Method::POST,
+        "/api/v1/statuses",
+        MockResponse::new(422).with_json(&json!({
+            "error": "Validation failed: Text can't be blank",
+            "details": { "text": [{ "error": "ERR_BLANK", "description": "can't be blank" }] }
+        })),
+    );
+    transport.push(
+        Method::GET,
+        "/api/v1/instance",
+        MockResponse::new(429)
+            .with_header("X-RateLimit-Limit", "300")
+            .with_header("X-RateLimit-Remaining", "0")
+            .with_header("X-RateLimit-Reset", "2030-01-01T00:00:00.000Z")
+            .with_json(&json!({ "error": "Too many requests" })),
+    );
+
+    let client = MastodonClient::new("https://mastodon.example")
+        .with_token("secret")
+        .with_retry_policy(RetryPolicy::none())
+        .with_transport(transport.clone());
+
+    let err = client.statuses().get("1").await.unwrap_err();
+    assert!(
+        matches!(&err, MastodonError::NotFound { response } if response.error == "Record not found")
+    );
+
+    let err = client.statuses().create_simple("").await.unwrap_err();
+    match err {
+        MastodonError::Unprocessable { details, .. } => {
+            assert_eq!(details["text"][0].error, "ERR_BLANK");
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+
+    // Statuses without a dedicated variant keep the response too.
+    transport.push(
+        Method::GET,
+        "/api/v1/statuses/2",
+        MockResponse::new(502)
+            .with_header("X-Request-Id", "req-502")
+            .with_json(&json!({ "error": "Bad gateway" })),
+    );
+    let err = client.statuses().get("2").await.unwrap_err();
+    assert!(matches!(err, MastodonError::ApiError { .. }));
+    assert_eq!(err.response().unwrap().request_id(), Some("req-502"));
+
+    let err = client.instance().get().await.unwrap_err();
+    assert!(matches!(
+        err,
+        MastodonError::RateLimited {
+            reset_at: Some(_),
+            ..
+        }
+    ));
+    assert_eq!(err.rate_limit().unwrap().remaining, 0);
+
+    // Without a token, a 401 points at the missing credentials.
+    transport.push(
+        Method::GET,
+        "/api/v1/accounts/verify_credentials",
+        MockResponse::new(401).with_json(&json!({ "error": "The access token is invalid" })),
+    );
+    let anonymous = MastodonClient::new("https://mastodon.example").with_transport(transport);
+    let err = anonymous.accounts().verify_credentials().await.unwrap_err();
+    assert!(matches!(err, MastodonError::AuthError { .. }));
+    assert_eq!(err.response().unwrap().error, "The access token is invalid");
+}
+
+#[tokio::test]
+async fn test_no_content_endpoints() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::POST,
+        "/api/v1/notifications/clear",
+        MockResponse::json(&json!({})),
+    );
+    transport.push(Method::DELETE, "/api/v1/lists/1", MockResponse::new(200));
+    transport.push(
+        Method::DELETE,
+        "/api/v1/push/subscription",
+        MockResponse::new(204),
+    );
+    transport.push(
+        Method::POST,
+        "/api/v1/domain_blocks",
+        MockResponse::new(200).with_body("<html>maintenance</html>"),
+    );
+
+    let client = MastodonClient::new("https://mastodon.example").with_transport(transport);
+
+    client.notifications().clear().await.unwrap();
+    client.lists().delete("1").await.unwrap();
+    client.push().unsubscribe().await.unwrap();
+    let err = client
+        .domain_blocks()
+        .block("spam.example")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, MastodonError::Serde(_)));
+}
+
+#[tokio::test]
+async fn test_response_metadata() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::GET,
+        "/api/v1/timelines/home",
+        MockResponse::json(&json!([status_json()]))
+            .with_header(
+                "Link",
+                "<https://mastodon.example/api/v1/timelines/home?max_id=100>; rel=\"next\", \
+                 <https://mastodon.example/api/v1/timelines/home?min_id=100>; rel=\"prev\"",
+            )
+            .with_header("X-RateLimit-Limit", "300")
+            .with_header("X-RateLimit-Remaining", "299")
+            .with_header("X-Request-Id", "req-42"),
+    );
+
+    let client = MastodonClient::new("https://mastodon.example").with_transport(transport);
+    let response = client.timelines().home_with_meta().await.unwrap();
+
+    assert_eq!(response.body.len(), 1);
+    assert_eq!(response.status, 200);
+    assert_eq!(
+        response.next_link().as_deref(),
+        Some("https://mastodon.example/api/v1/timelines/home?max_id=100")
+    );
+    assert_eq!(
+        response.prev_link().as_deref(),
+        Some("https://mastodon.example/api/v1/timelines/home?min_id=100")
+    );
+    assert_eq!(response.rate_limit().unwrap().remaining, 299);
+    assert_eq!(response.request_id(), Some("req-42"));
+}
+
+#[tokio::test]
+async fn test_response_cache() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::GET,
+        "/api/v1/custom_emojis",
+        MockResponse::json(&json!([])).with_header("ETag", "W/\"abc\""),
+    );
+    transport.push(
+        Method::GET,
+        "/api/v1/custom_emojis",
+        MockResponse::new(304).with_header("ETag", "W/\"abc\""),
+    );
+    transport.push(
+        Method::GET,
+        "/api/v1/instance/rules",
+        MockResponse::json(&json!([])),
+    );
+
+    let cache = ResponseCache::new().ttl("/api/v1/instance/rules", Duration::from_secs(60));
+    let client = MastodonClient::new("https://mastodon.example")
+        .with_cache(cache.clone())
+        .with_transport(transport.clone());
+
+    assert!(client.emojis().list().await.unwrap().is_empty());
+    // Revalidated with the ETag and answered with 304, served from the cache.
+    assert!(client.emojis().list().await.unwrap().is_empty());
+    let requests = transport.requests();
+    assert!(!requests[0].headers.contains_key("if-none-match"));
+    assert_eq!(requests[1].headers["if-none-match"], "W/\"abc\"");
+
+    // Within the TTL, the second call does not reach the transport.
+    client.instance().rules().await.unwrap();
+    client.instance().rules().await.unwrap();
+    assert_eq!(transport.requests().len(), 3);
+
+    // A clone switched to another account shares the cache, but not its entries.
+    transport.push(
+        Method::GET,
+        "/api/v1/instance/rules",
+        MockResponse::json(&json!([])),
+    );
+    let other = client.clone().with_token("other-token");
+    other.instance().rules().await.unwrap();
+    other.instance().rules().await.unwrap();
+    assert_eq!(transport.requests().len(), 4);
+    assert_eq!(cache.len(), 3);
+
+    cache.invalidate("/api/v1/instance/rules");
+    assert!(client.instance().rules().await.is_err());
+    assert_eq!(cache.len(), 1);
+}
+
+/// Clears the cache whenever a request revalidates a cached response.
+struct ClearOnRevalidate(ResponseCache);
+
+#[async_trait]
+impl Middleware for ClearOnRevalidate {
+    async fn before_request(&self, request: &mut reqwest::Request) -> mastodon_api::Result<()> {
+        if request.headers().contains_key("if-none-match") {
+            self.0.clear();
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_response_cache_entry_evicted_during_revalidation() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::GET,
+        "/api/v1/custom_emojis",
+        MockResponse::json(&json!([])).with_header("ETag", "W/\"abc\""),
+    );
+    transport.push(
+        Method::GET,
+        "/api/v1/custom_emojis",
+        MockResponse::new(304).with_header("ETag", "W/\"abc\""),
+    );
+    transport.push(
+        Method::GET,
+        "/api/v1/custom_emojis",
+        MockResponse::json(&json!([])).with_header("ETag", "W/\"abc\""),
+    );
+
+    let cache = ResponseCache::new();
+    let client = MastodonClient::new("https://mastodon.example")
+        .with_cache(cache.clone())
+        .with_middleware(ClearOnRevalidate(cache.clone()))
+        .with_transport(transport.clone());
+
+    client.emojis().list().await.unwrap();
+    // The 304 has no entry left to resolve to, so the request is repeated without validators.
+    assert!(client.emojis().list().await.unwrap().is_empty());
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[1].headers["if-none-match"], "W/\"abc\"");
+    assert!(!requests[2].headers.contains_key("if-none-match"));
+    assert_eq!(cache.len(), 1);
+}
+
+#[tokio::test]
+async fn test_account_pool() {
+    let mut server = Server::new_async().await;
+    let url = server.url();
+
+    let _m = server
+        .mock("GET", "/api/v1/domain_blocks")
+        .match_header("authorization", "Bearer token_a")
+        .with_status(200)
+        .with_header("X-RateLimit-Limit", "300")
+        .with_header("X-RateLimit-Remaining", "120")
+        .with_body("[]")
+        .create_async()
+        .await;
+
+    let pool = AccountPool::new().configure(|builder| builder.user_agent("pool-test/1.0"));
+    pool.add_with_url("@news@Example.org", &url, "token_a")
+        .unwrap();
+    pool.add_with_url("weather@example.org", &url, "token_b")
+        .unwrap();
+    assert!(pool.add("not-a-handle", "token").is_err());
+
+    let news = pool.get("news@example.org").unwrap();
+    news.domain_blocks().list().await.unwrap();
+
+    // Rate limits are tracked per account.
+    assert_eq!(news.rate_limit().unwrap().remaining, 120);
+    assert!(
+        pool.get("weather@example.org")
+            .unwrap()
+            .rate_limit()
+            .is_none()
+    );
+
+    assert_eq!(
+        pool.handles(),
+        vec![
+            "news@example.org".to_string(),
+            "weather@example.org".to_string()
+        ]
+    );
+    assert!(pool.remove("weather@example.org").is_some());
+    assert!(!pool.contains("weather@example.org"));
+    assert_eq!(pool.len(), 1);
+}
+
+#[tokio::test]
+async fn test_outbox_delivers_thread_in_order() {
+    let path = std::env::temp_dir().join(format!("mastodon-outbox-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let transport = Arc::new(MockTransport::new());
+    let client = MastodonClient::new("https://mastodon.example")
+        .with_token("secret")
+        .with_retry_policy(RetryPolicy::none())
+        .with_transport(transport.clone());
+
+    let outbox = Outbox::open(&path).unwrap();
+    let first = outbox
+        .enqueue(CreateStatusParams {
+            status: "1/2".to_string(),
+            media_ids: vec!["77".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+    outbox
+        .enqueue_reply(
+            first,
+            CreateStatusParams {
+                status: "2/2".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    // The instance is in maintenance: nothing is delivered and the order is kept.
+    transport.push(Method::POST, "/api/v1/statuses", MockResponse::new(503));
+    let report = outbox.flush(&client).await.unwrap();
+    assert!(report.delivered.is_empty());
+    assert_eq!(report.pending, 2);
+    drop(outbox);
+
+    // The queue survives a restart.
+    let outbox = Outbox::open(&path).unwrap();
+    assert_eq!(outbox.pending()[0].attempts, 1);
+    let mut reply = status_json();
+    reply["id"] = json!("101");
+    transport.push(
+        Method::POST,
+        "/api/v1/statuses",
+        MockResponse::json(&status_json()),
+    );
+    transport.push(Method::POST, "/api/v1/statuses", MockResponse::json(&reply));
+    let report = outbox.flush(&client).await.unwrap();
+    assert_eq!(report.delivered.len(), 2);
+    assert_eq!(report.pending, 0);
+    assert!(outbox.is_empty());
+
+    let requests = transport.requests();
+    assert_eq!(
+        requests[0].headers["idempotency-key"],
+        requests[1].headers["idempotency-key"]
+    );
+    let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+    assert_eq!(body["media_ids"], json!(["77"]));
+    let body: serde_json::Value = serde_json::from_slice(&requests[2].body).unwrap();
+    assert_eq!(body["in_reply_to_id"], "100");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_outbox_enqueue_fails_when_unwritable() {
+    let path = std::env::temp_dir()
+        .join(format!("mastodon-outbox-missing-{}", std::process::id()))
+        .join("outbox.json");
+    let outbox = Outbox::open(&path).unwrap();
+    let result = outbox.enqueue(CreateStatusParams {
+        status: "Hello".to_string(),
+        ..Default::default()
+    });
+    assert!(result.is_err());
+    assert!(outbox.is_empty());
+
+    // The failed entry is not posted behind the caller's back.
+    let transport = Arc::new(MockTransport::new());
+    let client = MastodonClient::new("https://mastodon.example")
+        .with_token("secret")
+        .with_transport(transport.clone());
+    let report = outbox.flush(&client).await.unwrap();
+    assert!(report.delivered.is_empty());
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_outbox_flush_in_dry_run_keeps_entries() {
+    let path = std::env::temp_dir().join(format!(
+        "mastodon-outbox-dry-run-{}.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    let outbox = Outbox::open(&path).unwrap();
+    outbox
+        .enqueue(CreateStatusParams {
+            status: "Hello".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+    let before = std::fs::read(&path).unwrap();
+
+    let transport = Arc::new(MockTransport::new());
+    let client = MastodonClient::builder("https://mastodon.example")
+        .token("secret")
+        .transport(transport.clone())
+        .dry_run(true)
+        .build()
+        .unwrap();
+    let report = outbox.deliver(&client).await.unwrap();
+
+    assert!(report.delivered.is_empty());
+    assert!(report.failed.is_empty());
+    assert_eq!(report.pending, 1);
+    assert_eq!(outbox.pending()[0].attempts, 0);
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+    assert!(transport.requests().is_empty());
+    assert_eq!(client.dry_run_log().entries().len(), 1);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Transport that answers after a short delay and tracks how many requests overlap.
+#[derive(Default)]
+struct SlowTransport {
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+#[async_trait]
+impl Transport for SlowTransport {
+    async fn send(&self, request: reqwest::Request) -> mastodon_api::Result<HttpResponse> {
+        let current = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(current, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        Ok(HttpResponse {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            status: reqwest::StatusCode::OK,
+            headers: Default::default(),
+            body: b"[]".to_vec(),
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_concurrency_limiter() {
+    let transport = Arc::new(SlowTransport::default());
+    let client = MastodonClient::new("https://mastodon.example")
+        .with_concurrency_limiter(ConcurrencyLimiter::new().max_in_flight(8).max_per_host(2))
+        .with_transport(transport.clone());
+
+    let tasks: Vec<_> = (0..6)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move { client.domain_blocks().list().await })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+
+    assert_eq!(transport.max_in_flight.load(Ordering::SeqCst), 2);
+    assert_eq!(client.concurrency_limiter().available(), Some(8));
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_blocking_client() {
+    use mastodon_api::blocking::BlockingMastodonClient;
+
+    let mut server = Server::new();
+    let m = server
+        .mock("POST", "/api/v1/statuses")
+        .match_header("authorization", "Bearer secret")
+        .with_status(200)
+        .with_body(status_json().to_string())
+        .create();
+    let _clear = server
+        .mock("POST", "/api/v1/notifications/clear")
+        .with_status(200)
+        .with_body("{}")
+        .create();
+    let resolve = server
+        .mock("POST", "/api/v1/admin/reports/7/resolve")
+        .with_status(200)
+        .with_body("{}")
+        .create();
+
+    let client = BlockingMastodonClient::new(&server.url()).with_token("secret");
+    let status = client.statuses().create_simple("Hello").unwrap();
+    assert_eq!(status.id, "100");
+    client.notifications().clear().unwrap();
+    // Admin endpoints are grouped like those of the async client.
+    client.admin().reports().resolve("7").unwrap();
+    m.assert();
+    resolve.assert();
+}
+
+#[tokio::test]
+async fn test_capability_detection() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::GET,
+        "/api/v1/instance",
+        MockResponse::json(&json!({
+            "uri": "glitch.example",
+            "title": "Glitch",
+            "description": "",
+            "email": "admin@glitch.example",
+            "version": "3.5.3+glitch"
+        })),
+    );
+    let client = MastodonClient::new("https://glitch.example").with_transport(transport.clone());
+
+    let capabilities = client.detect_capabilities().await.unwrap();
+    let version = capabilities.version().unwrap();
+    assert_eq!((version.major, version.minor, version.patch), (3, 5, 3));
+    assert_eq!(version.suffix.as_deref(), Some("+glitch"));
+    assert!(capabilities.supports(Feature::TrendingStatuses));
+
+    // Rejected before a request is sent.
+    let err = client.tags().list_followed().await.unwrap_err();
+    assert!(matches!(
+        err,
+        MastodonError::Unsupported {
+            feature: Feature::FollowedTags,
+            ..
+        }
+    ));
+    assert_eq!(transport.requests().len(), 1);
+
+    // The fork backported followed tags.
+    client.set_feature(Feature::FollowedTags, true);
+    transport.push(
+        Method::GET,
+        "/api/v1/followed_tags",
+        MockResponse::json(&json!([])),
+    );
+    assert!(client.tags().list_followed().await.unwrap().is_empty());
+
+    let compatible = ServerVersion::parse("2.7.2 (compatible; Pleroma 2.5.0)").unwrap();
+    assert!(compatible.at_least(2, 7, 0));
+    assert_eq!(compatible.to_string(), "2.7.2 (compatible; Pleroma 2.5.0)");
+}
+
+#[tokio::test]
+async fn test_dry_run() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::GET,
+        "/api/v1/statuses/100",
+        MockResponse::json(&status_json()),
+    );
+    let counter = Arc::new(CountingMiddleware::default());
+    let client = MastodonClient::new("https://mastodon.example")
+        .with_token("secret")
+        .with_dry_run(true)
+        .with_middleware(counter.clone())
+        .with_transport(transport.clone());
+
+    // Reads go through.
+    let status = client.statuses().get("100").await.unwrap();
+
+    let err = client.statuses().delete(&status.id).await.unwrap_err();
+    assert!(matches!(err, MastodonError::DryRun { ref method, .. } if method == Method::DELETE));
+    client.admin().accounts().suspend("42").await.unwrap();
+
+    assert_eq!(transport.requests().len(), 1);
+    let planned = client.dry_run_log().entries();
+    assert_eq!(planned.len(), 2);
+    assert_eq!(planned[0].url.path(), "/api/v1/statuses/100");
+    assert_eq!(planned[1].url.path(), "/api/v1/admin/accounts/42/action");
+    assert_eq!(planned[1].body_text(), "type=suspend");
+    // Middleware only sees the dry run that failed the call; the suspension succeeded.
+    assert_eq!(counter.errors.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_cassette_record_and_replay() {
+    let path = std::env::temp_dir().join(format!("mastodon-cassette-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::GET,
+        "/api/v1/timelines/public",
+        MockResponse::json(&json!([status_json()])).with_header(
+            "Link",
+            "<https://mastodon.example/api/v1/timelines/public?max_id=100>; rel=\"next\"",
+        ),
+    );
+    transport.push(
+        Method::GET,
+        "/api/v1/timelines/public",
+        MockResponse::json(&json!([])),
+    );
+    transport.push(
+        Method::POST,
+        "/api/v1/statuses",
+        MockResponse::json(&status_json()),
+    );
+
+    let recorder = Arc::new(CassetteTransport::record(&path, transport.clone()));
+    let client = MastodonClient::new("https://mastodon.example")
+        .with_token("secret")
+        .with_transport(recorder.clone());
+    let timelines = client.timelines();
+    let mut pages = timelines.public_paged().await.unwrap();
+    assert_eq!(pages.next_page().await.unwrap().unwrap().len(), 1);
+    assert!(pages.next_page().await.unwrap().unwrap().is_empty());
+    client.statuses().create_simple("Hello").await.unwrap();
+    assert_eq!(transport.pending(), 0);
+
+    // Nothing is written while requests are recorded.
+    assert!(!path.exists());
+    recorder.save().unwrap();
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    assert!(!cassette.contains("secret"));
+
+    // The replay targets another host and never reaches the mock transport.
+    let player = CassetteTransport::replay(&path).unwrap();
+    let client = MastodonClient::new("https://offline.example")
+        .with_token("other")
+        .with_retry_policy(RetryPolicy::none())
+        .with_transport(player);
+    let timelines = client.timelines();
+    let mut pages = timelines.public_paged().await.unwrap();
+    let first = pages.next_page().await.unwrap().unwrap();
+    assert_eq!(first[0].id, "100");
+    assert!(pages.next_page().await.unwrap().unwrap().is_empty());
+    let status = client.statuses().create_simple("Hello").await.unwrap();
+    assert_eq!(status.id, "100");
+
+    // Every interaction is served once, and unrecorded requests fail.
+    assert!(matches!(
+        client.statuses().create_simple("Hello").await,
+        Err(MastodonError::Custom(_))
+    ));
+    assert!(matches!(
+        client.timelines().home().await,
+        Err(MastodonError::Custom(_))
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_oauth_authorization_code_flow() {
+    use base64::Engine;
+    use sha2::{Digest, Sha256};
+
+    let transport = Arc::new(MockTransport::new());
+    let token = json!({
+        "access_token": "user-token",
+        "token_type": "Bearer",
+        "scope": "read write",
+        "created_at": 1_700_000_000,
+        "refresh_token": "refresh-me",
+        "expires_in": 3600
+    });
+    transport.push(Method::POST, "/oauth/token", MockResponse::json(&token));
+    transport.push(
+        Method::GET,
+        "/api/v1/accounts/verify_credentials",
+        MockResponse::json(&account_json())
+            .with_header("X-RateLimit-Limit", "300")
+            .with_header("X-RateLimit-Remaining", "299"),
+    );
+    transport.push(Method::POST, "/oauth/token", MockResponse::json(&token));
+    transport.push(
+        Method::POST,
+        "/oauth/revoke",
+        MockResponse::json(&json!({})),
+    );
+
+    let client = MastodonClient::new("https://mastodon.example").with_transport(transport.clone());
+    let oauth = OAuth::new(
+        client,
+        "app-id",
+        "app-secret",
+        "https://bot.example/callback",
+    )
+    .scopes("read write")
+    .force_login(true);
+
+    let request = oauth.authorize_url().unwrap();
+    assert_eq!(request.url.path(), "/oauth/authorize");
+    let query: std::collections::HashMap<_, _> = request.url.query_pairs().into_owned().collect();
+    assert_eq!(query["client_id"], "app-id");
+    assert_eq!(query["scope"], "read write");
+    assert_eq!(query["state"], request.state);
+    assert_eq!(query["force_login"], "true");
+    assert_eq!(query["code_challenge_method"], "S256");
+    let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .encode(Sha256::digest(request.code_verifier.as_bytes()));
+    assert_eq!(query["code_challenge"], challenge);
+    assert_ne!(request.state, oauth.authorize_url().unwrap().state);
+
+    let (user, issued) = oauth
+        .authenticate("the-code", &request.code_verifier)
+        .await
+        .unwrap();
+    assert_eq!(issued.refresh_token.as_deref(), Some("refresh-me"));
+    assert_eq!(user.access_token(), Some("user-token"));
+    user.accounts().verify_credentials().await.unwrap();
+    assert_eq!(user.rate_limit().unwrap().remaining, 299);
+    // Clients for other tokens start with their own rate limit state.
+    assert!(oauth.client_for(&issued).rate_limit().is_none());
+
+    oauth.refresh("refresh-me").await.unwrap();
+    oauth.revoke("user-token").await.unwrap();
+
+    let requests = transport.requests();
+    let form = |index: usize| -> std::collections::HashMap<String, String> {
+        url::form_urlencoded::parse(&requests[index].body)
+            .into_owned()
+            .collect()
+    };
+    let exchange = form(0);
+    assert_eq!(exchange["grant_type"], "authorization_code");
+    assert_eq!(exchange["code"], "the-code");
+    assert_eq!(exchange["code_verifier"], request.code_verifier);
+    assert_eq!(exchange["redirect_uri"], "https://bot.example/callback");
+    assert!(!requests[0].headers.contains_key("authorization"));
+    assert_eq!(requests[1].headers["authorization"], "Bearer user-token");
+    assert_eq!(form(2)["grant_type"], "refresh_token");
+    assert_eq!(form(2)["refresh_token"], "refresh-me");
+    assert_eq!(form(3)["token"], "user-token");
+}
+
+#[tokio::test]
+async fn test_client_credentials_app_token() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::POST,
+        "/oauth/token",
+        MockResponse::json(&json!({
+            "access_token": "app-token",
+            "token_type": "Bearer",
+            "scope": "read",
+            "created_at": 1_700_000_000
+        })),
+    );
+    transport.push(
+        Method::GET,
+        "/api/v1/apps/verify_credentials",
+        MockResponse::json(&json!({
+            "name": "Public Reader",
+            "website": null,
+            "scopes": ["read"],
+            "redirect_uris": ["urn:ietf:wg:oauth:2.0:oob"]
+        })),
+    );
+
+    let client = MastodonClient::new("https://mastodon.example").with_transport(transport.clone());
+    let oauth = OAuth::new(
+        client,
+        "app-id",
+        "app-secret",
+        mastodon_api::oauth::OUT_OF_BAND,
+    );
+    let (app_client, token) = oauth.authenticate_app().await.unwrap();
+    assert_eq!(token.access_token, "app-token");
+    assert!(token.refresh_token.is_none());
+
+    let application = app_client.apps().verify_credentials().await.unwrap();
+    assert_eq!(application.name, "Public Reader");
+    assert_eq!(application.scopes, vec!["read"]);
+
+    let requests = transport.requests();
+    let form: std::collections::HashMap<String, String> =
+        url::form_urlencoded::parse(&requests[0].body)
+            .into_owned()
+            .collect();
+    assert_eq!(form["grant_type"], "client_credentials");
+    assert_eq!(form["client_secret"], "app-secret");
+    assert_eq!(form["scope"], "read");
+    assert!(!form.contains_key("code"));
+    assert_eq!(requests[1].headers["authorization"], "Bearer app-token");
+}
+
+#[tokio::test]
+async fn test_loopback_login() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::POST,
+        "/api/v1/apps",
+        MockResponse::json(&json!({
+            "id": "1",
+            "name": "CLI",
+            "website": null,
+            "redirect_uri": "http://127.0.0.1/callback",
+            "client_id": "app-id",
+            "client_secret": "app-secret",
+            "vapid_key": null
+        })),
+    );
+    transport.push(
+        Method::POST,
+        "/oauth/token",
+        MockResponse::json(&json!({
+            "access_token": "user-token",
+            "token_type": "Bearer",
+            "scope": "read write",
+            "created_at": 1_700_000_000
+        })),
+    );
+
+    let client = MastodonClient::new("https://mastodon.example").with_transport(transport.clone());
+    let login = LoopbackLogin::new(client.clone(), "CLI")
+        .scopes("read write")
+        .start()
+        .await
+        .unwrap();
+    let redirect_uri = login.redirect_uri().to_string();
+    assert!(redirect_uri.starts_with("http://127.0.0.1:"));
+    let state = login
+        .authorize_url()
+        .query_pairs()
+        .find(|(name, _)| name == "state")
+        .unwrap()
+        .1
+        .into_owned();
+    let registration: serde_json::Value =
+        serde_json::from_slice(&transport.requests()[0].body).unwrap();
+    assert_eq!(registration["redirect_uris"], redirect_uri);
+
+    let finished = tokio::spawn(login.finish());
+    // A connection the browser opened but never used does not hold up later requests.
+    let address = redirect_uri
+        .trim_start_matches("http://")
+        .trim_end_matches("/callback");
+    let _idle = tokio::net::TcpStream::connect(address).await.unwrap();
+    let browser = reqwest::Client::new();
+    let favicon = redirect_uri.replace("/callback", "/favicon.ico");
+    let response = tokio::time::timeout(Duration::from_secs(5), browser.get(&favicon).send())
+        .await
+        .unwrap();
+    assert_eq!(response.unwrap().status(), 404);
+    let page = browser
+        .get(&redirect_uri)
+        .query(&[("code", "the-code"), ("state", state.as_str())])
+        .send()
+        .await
+        .unwrap();
+    assert!(page.status().is_success());
+
+    let (user, token) = finished.await.unwrap().unwrap();
+    assert_eq!(token.access_token, "user-token");
+    assert_eq!(user.access_token(), Some("user-token"));
+    let exchange: std::collections::HashMap<String, String> =
+        url::form_urlencoded::parse(&transport.requests()[1].body)
+            .into_owned()
+            .collect();
+    assert_eq!(exchange["code"], "the-code");
+    assert_eq!(exchange["redirect_uri"], redirect_uri);
+
+    // A redirect with a foreign state is rejected without ending the login.
+    let app = registered_app();
+    let login = LoopbackLogin::new(client, "CLI")
+        .app(app)
+        .start()
+        .await
+        .unwrap();
+    let redirect_uri = login.redirect_uri().to_string();
+    let finished = tokio::spawn(login.finish());
+    let page = browser
+        .get(&redirect_uri)
+        .query(&[("code", "stolen"), ("state", "forged")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(page.status(), 400);
+    assert!(!finished.is_finished());
+
+    // Only a denial ends it early, and no code is exchanged.
+    let page = browser
+        .get(&redirect_uri)
+        .query(&[("error", "access_denied")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(page.status(), 400);
+    assert!(matches!(
+        finished.await.unwrap(),
+        Err(MastodonError::AuthError { .. })
+    ));
+    assert_eq!(transport.requests().len(), 2);
+}
+
+fn registered_app() -> mastodon_api::methods::apps::AppRegistration {
+    serde_json::from_value(json!({
+        "id": "1",
+        "name": "CLI",
+        "redirect_uri": "http://127.0.0.1/callback",
+        "client_id": "app-id",
+        "client_secret": "app-secret"
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_credential_store() {
+    let path =
+        std::env::temp_dir().join(format!("mastodon-credentials-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::POST,
+        "/api/v1/apps",
+        MockResponse::json(&json!({
+            "id": "1",
+            "name": "Bot",
+            "website": null,
+            "redirect_uri": "urn:ietf:wg:oauth:2.0:oob",
+            "client_id": "app-id",
+            "client_secret": "app-secret",
+            "vapid_key": null
+        })),
+    );
+    let client = MastodonClient::new("https://mastodon.example/").with_transport(transport.clone());
+    let params = RegisterAppParams {
+        client_name: "Bot".to_string(),
+        redirect_uris: mastodon_api::oauth::OUT_OF_BAND.to_string(),
+        scopes: "read write".to_string(),
+        website: None,
+    };
+
+    let store = JsonFileStore::open(&path).unwrap();
+    let app = client.load_or_register_app(&store, &params).await.unwrap();
+    assert_eq!(app.client_id, "app-id");
+    let token = serde_json::from_value(json!({
+        "access_token": "bot-token",
+        "token_type": "Bearer",
+        "scope": "read write",
+        "created_at": 1_700_000_000
+    }))
+    .unwrap();
+    store
+        .save_token(client.base_url(), "@bot", &token)
+        .await
+        .unwrap();
+
+    // A fresh process finds both in the file and does not register again.
+    let store = JsonFileStore::open(&path).unwrap();
+    assert_eq!(store.instances(), vec!["https://mastodon.example"]);
+    let app = client.load_or_register_app(&store, &params).await.unwrap();
+    assert_eq!(app.client_secret, "app-secret");
+    assert_eq!(transport.requests().len(), 1);
+
+    let bot = client
+        .clone()
+        .with_stored_token(&store, "@bot")
+        .await
+        .unwrap();
+    assert_eq!(bot.access_token(), Some("bot-token"));
+    assert!(matches!(
+        MastodonClient::from_store("https://mastodon.example", &store, "@other").await,
+        Err(MastodonError::AuthError { .. })
+    ));
+
+    // A temporary file left over by an interrupted save does not pass on its mode.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, "{}").unwrap();
+        std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o644)).unwrap();
+    }
+    store.remove_token(client.base_url(), "@bot").await.unwrap();
+    assert!(store.accounts(client.base_url()).is_empty());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // A change that cannot be written is not kept in memory either.
+    let missing_dir = std::env::temp_dir().join(format!("mastodon-missing-{}", std::process::id()));
+    let unwritable = JsonFileStore::open(missing_dir.join("credentials.json")).unwrap();
+    assert!(
+        unwritable
+            .save_app(client.base_url(), &registered_app())
+            .await
+            .is_err()
+    );
+    assert!(unwritable.instances().is_empty());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_scope_preflight_checks() {
+    let scopes: Scopes = "read:statuses write:media follow".parse().unwrap();
+    assert!(scopes.allows(Scope::ReadStatuses));
+    assert!(scopes.allows(Scope::WriteFollows));
+    assert!(!scopes.allows(Scope::WriteStatuses));
+    assert!(Scope::Read.covers(Scope::ReadNotifications));
+    assert!(!Scope::Read.covers(Scope::AdminReadAccounts));
+    assert!(Scope::AdminWrite.covers(Scope::AdminWriteAccounts));
+    assert!("read:everything".parse::<Scopes>().is_err());
+    assert_eq!(
+        Scopes::parse_lenient("read future:scope").to_string(),
+        "read"
+    );
+
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::GET,
+        "/api/v1/timelines/home",
+        MockResponse::json(&json!([])),
+    );
+    transport.push(
+        Method::POST,
+        "/api/v1/accounts/1/follow",
+        MockResponse::json(&json!({ "id": "1", "following": true })),
+    );
+    transport.push(
+        Method::GET,
+        "/api/v1/reports",
+        MockResponse::json(&json!([])),
+    );
+    let counter = Arc::new(CountingMiddleware::default());
+    let client = MastodonClient::new("https://mastodon.example")
+        .with_token("secret")
+        .with_scopes(scopes)
+        .with_middleware(counter.clone())
+        .with_transport(transport.clone());
+
+    client.timelines().home().await.unwrap();
+    let err = client.statuses().create_simple("Hello").await.unwrap_err();
+    match &err {
+        MastodonError::MissingScope { scope, endpoint } => {
+            assert_eq!(*scope, Scope::WriteStatuses);
+            assert_eq!(endpoint, "POST /api/v1/statuses");
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(err.to_string().contains("write:statuses"));
+    assert!(matches!(
+        client.admin().reports().list().await,
+        Err(MastodonError::MissingScope {
+            scope: Scope::AdminReadReports,
+            ..
+        })
+    ));
+    let _ = client.accounts().follow("1").await;
+    // Listing reports needs no scope beyond a valid token.
+    client.reports().list().await.unwrap();
+
+    // Only the permitted requests reached the transport.
+    let paths: Vec<_> = transport
+        .requests()
+        .iter()
+        .map(|request| request.url.path().to_string())
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            "/api/v1/timelines/home",
+            "/api/v1/accounts/1/follow",
+            "/api/v1/reports"
+        ]
+    );
+    // Middleware is told about the rejected requests.
+    assert_eq!(counter.errors.load(Ordering::SeqCst), 2);
+
+    // Scopes belong to the token, so switching tokens forgets them.
+    assert!(client.scopes().is_some());
+    assert!(client.clone().with_token("other").scopes().is_none());
+
+    // Scopes from an OAuth token are recorded on the returned client.
+    let token: mastodon_api::oauth::Token = serde_json::from_value(json!({
+        "access_token": "t",
+        "token_type": "Bearer",
+        "scope": "read write",
+        "created_at": 0
+    }))
+    .unwrap();
+    assert!(token.scopes().unwrap().allows(Scope::WriteStatuses));
+
+    // Tokens with unknown or no scopes are not checked, as that could reject requests
+    // the token is allowed to make.
+    for scope in ["read:statuses custom:scope", ""] {
+        let token: mastodon_api::oauth::Token = serde_json::from_value(json!({
+            "access_token": "t",
+            "token_type": "Bearer",
+            "scope": scope,
+            "created_at": 0
+        }))
+        .unwrap();
+        assert!(token.scopes().is_none());
+
+        let transport = Arc::new(MockTransport::new());
+        transport.push(
+            Method::POST,
+            "/api/v1/statuses",
+            MockResponse::json(&status_json()),
+        );
+        let oauth = OAuth::new(
+            MastodonClient::new("https://mastodon.example").with_transport(transport.clone()),
+            "app-id",
+            "app-secret",
+            "https://bot.example/callback",
+        );
+        let client = oauth.client_for(&token);
+        assert!(client.scopes().is_none());
+        client.statuses().create_simple("Hello").await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_account_registration() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::POST,
+        "/api/v1/accounts",
+        MockResponse::json(&json!({
+            "access_token": "new-user-token",
+            "token_type": "Bearer",
+            "scope": "read write follow push",
+            "created_at": 1_700_000_000
+        })),
+    );
+    transport.push(
+        Method::POST,
+        "/api/v1/accounts",
+        MockResponse::new(422).with_json(&json!({
+            "error": "Validation failed: Username has already been taken, Password is too short",
+            "details": {
+                "username": [{ "error": "ERR_TAKEN", "description": "is already taken" }],
+                "password": [{ "error": "ERR_TOO_SHORT", "description": "is too short (minimum is 8 characters)" }]
+            }
+        })),
+    );
+    let client = MastodonClient::new("https://mastodon.example")
+        .with_token("app-token")
+        .with_scopes("read write:accounts".parse().unwrap())
+        .with_transport(transport.clone());
+
+    let mut params = RegisterAccountParams {
+        username: "tester".to_string(),
+        email: "tester@example.com".to_string(),
+        password: "correct horse battery staple".to_string(),
+        agreement: true,
+        locale: "en".to_string(),
+        reason: None,
+    };
+    let token = client.accounts().register(&params).await.unwrap();
+    assert_eq!(token.access_token, "new-user-token");
+    let body: serde_json::Value = serde_json::from_slice(&transport.requests()[0].body).unwrap();
+    assert_eq!(body["username"], "tester");
+    assert_eq!(body["agreement"], true);
+    assert!(body.get("reason").is_none());
+
+    params.username = "admin".to_string();
+    params.password = "short".to_string();
+    match client.accounts().register(&params).await {
+        Err(MastodonError::Unprocessable { details, .. }) => {
+            assert_eq!(details["username"][0].error, "ERR_TAKEN");
+            assert_eq!(details["password"][0].error, "ERR_TOO_SHORT");
+        }
+        other => panic!(
+            "unexpected result: {:?}",
+            other.map(|token| token.access_token)
+        ),
+    }
+}
+
+#[tokio::test]
+async fn test_full_status_model() {
+    let mut original = status_json();
+    original["id"] = json!("200");
+    original["media_attachments"] = json!([{
+        "id": "7",
+        "type": "image",
+        "url": "https://files.example/7.png",
+        "preview_url": "https://files.example/7-small.png",
+        "remote_url": null,
+        "description": "A cat",
+        "blurhash": "UFGuX%"
+    }, {
+        "id": "8",
+        "type": "video",
+        "url": null,
+        "preview_url": null,
+        "remote_url": null,
+        "description": null,
+        "blurhash": null
+    }]);
+    original["mentions"] = json!([{
+        "id": "2",
+        "username": "friend",
+        "url": "https://mastodon.example/@friend",
+        "acct": "friend"
+    }]);
+    original["tags"] = json!([{ "name": "cats", "url": "https://mastodon.example/tags/cats" }]);
+    original["emojis"] = json!([{
+        "shortcode": "blobcat",
+        "url": "https://files.example/blobcat.png",
+        "static_url": "https://files.example/blobcat.png",
+        "visible_in_picker": true
+    }]);
+    original["poll"] = json!({
+        "id": "34",
+        "expires_at": "2026-10-18T00:00:00.000Z",
+        "expired": false,
+        "multiple": false,
+        "votes_count": 10,
+        "voters_count": 10,
+        "options": [{ "title": "Yes", "votes_count": 7 }, { "title": "No", "votes_count": 3 }],
+        "emojis": [],
+        "voted": true,
+        "own_votes": [0]
+    });
+    original["card"] = json!({
+        "url": "https://blog.example/post",
+        "title": "A post",
+        "description": "",
+        "type": "link",
+        "image": null
+    });
+    original["application"] = json!({ "name": "Web", "website": null });
+    original["edited_at"] = json!("2026-10-17T12:00:00.000Z");
+    original["favourited"] = json!(true);
+    original["bookmarked"] = json!(false);
+    original["filtered"] = json!([{
+        "filter": {
+            "id": "3",
+            "title": "Spoilers",
+            "context": ["home"],
+            "expires_at": null,
+            "filter_action": "warn"
+        },
+        "keyword_matches": ["ending"],
+        "status_matches": null
+    }]);
+
+    let mut boost = status_json();
+    boost["reblog"] = original;
+    boost["reblogged"] = json!(true);
+
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::GET,
+        "/api/v1/statuses/100",
+        MockResponse::json(&boost),
+    );
+    let client = MastodonClient::new("https://mastodon.example").with_transport(transport);
+    let status = client.statuses().get("100").await.unwrap();
+
+    assert_eq!(status.reblogged, Some(true));
+    assert!(status.media_attachments.is_empty());
+    let original = status.original();
+    assert_eq!(original.id, "200");
+    assert_eq!(original.media_attachments[0].media_type, "image");
+    // Media still being processed has no URL yet.
+    assert_eq!(original.media_attachments[1].url, None);
+    assert_eq!(original.media_attachments[1].preview_url, None);
+    assert_eq!(original.mentions[0].acct, "friend");
+    assert_eq!(original.tags[0].name, "cats");
+    assert_eq!(original.emojis[0].shortcode, "blobcat");
+    let poll = original.poll.as_ref().unwrap();
+    assert_eq!(poll.options[0].votes_count, Some(7));
+    assert_eq!(poll.own_votes, Some(vec![0]));
+    assert_eq!(original.card.as_ref().unwrap().card_type, "link");
+    assert_eq!(original.application.as_ref().unwrap().name, "Web");
+    assert!(original.edited_at.is_some());
+    assert_eq!(original.favourited, Some(true));
+    assert_eq!(original.pinned, None);
+    assert_eq!(original.filtered[0].filter.filter_action, "warn");
+
+    // Serializing keeps every field, so statuses can be cached as JSON.
+    let value = serde_json::to_value(&status).unwrap();
+    let parsed: mastodon_api::models::Status = serde_json::from_value(value).unwrap();
+    assert_eq!(parsed.original().poll.as_ref().unwrap().id, "34");
+}
+
+#[tokio::test]
+async fn test_full_account_model() {
+    let mut moved_to = account_json();
+    moved_to["id"] = json!("9");
+    moved_to["acct"] = json!("bot@new.example");
+
+    let mut account = account_json();
+    account["avatar_static"] = json!("https://mastodon.social/avatar-static.png");
+    account["header_static"] = json!("https://mastodon.social/header-static.png");
+    account["fields"] = json!([
+        { "name": "Website", "value": "<a href=\"https://bot.example\">bot.example</a>", "verified_at": "2026-01-02T00:00:00.000Z" },
+        { "name": "Owner", "value": "@someone", "verified_at": null }
+    ]);
+    account["emojis"] = json!([]);
+    account["moved"] = moved_to;
+    account["discoverable"] = json!(null);
+    account["group"] = json!(false);
+    account["noindex"] = json!(true);
+    account["suspended"] = json!(true);
+    account["last_status_at"] = json!("2026-10-16");
+    account["roles"] = json!([{ "id": "3", "name": "Owner", "color": "#ff3838" }]);
+    account["source"] = json!({
+        "note": "A bot",
+        "fields": [{ "name": "Website", "value": "https://bot.example", "verified_at": null }],
+        "privacy": "unlisted",
+        "sensitive": false,
+        "language": "en",
+        "follow_requests_count": 2,
+        "indexable": false
+    });
+
+    let transport = Arc::new(MockTransport::new());
+    transport.push(
+        Method::GET,
+        "/api/v1/accounts/verify_credentials",
+        MockResponse::json(&account),
+    );
+    let client = MastodonClient::new("https://mastodon.example")
+        .with_token("secret")
+        .with_transport(transport);
+    let account = client.accounts().verify_credentials().await.unwrap();
+
+    assert!(account.fields[0].verified_at.is_some());
+    assert!(account.fields[1].verified_at.is_none());
+    assert_eq!(account.moved.as_ref().unwrap().acct, "bot@new.example");
+    assert_eq!(account.discoverable, None);
+    assert_eq!(account.noindex, Some(true));
+    assert_eq!(account.suspended, Some(true));
+    assert_eq!(account.limited, None);
+    assert_eq!(account.last_status_at.as_deref(), Some("2026-10-16"));
+    assert_eq!(account.roles[0].color, "#ff3838");
+    let source = account.source.unwrap();
+    assert_eq!(source.privacy, "unlisted");
+    assert_eq!(source.follow_requests_count, 2);
+    assert_eq!(source.fields[0].value, "https://bot.example");
+
+    // Other users' accounts come without `source`.
+    let minimal: mastodon_api::models::Account = serde_json::from_value(account_json()).unwrap();
+    assert!(minimal.source.is_none() && minimal.fields.is_empty());
+}